@@ -0,0 +1,31 @@
+-- Журнал завершених ігор: гра, її учасники та ходи
+CREATE TABLE IF NOT EXISTS matches (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    room TEXT NOT NULL,
+    seed BIGINT NOT NULL, -- u64 зберігається побітово
+    rules JSONB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS match_players (
+    id BIGSERIAL PRIMARY KEY,
+    match BIGINT NOT NULL REFERENCES matches (id) ON UPDATE NO ACTION ON DELETE CASCADE,
+    player UUID NOT NULL, -- боти не мають акаунтів, тому зовнішнього ключа немає
+    place SMALLINT NOT NULL,
+    points BIGINT NOT NULL,
+    cards_had INTEGER NOT NULL,
+    UNIQUE (match, player)
+);
+
+CREATE INDEX IF NOT EXISTS match_players_player_idx ON match_players (player, match DESC);
+
+CREATE TABLE IF NOT EXISTS match_moves (
+    id BIGSERIAL PRIMARY KEY,
+    match BIGINT NOT NULL REFERENCES matches (id) ON UPDATE NO ACTION ON DELETE CASCADE,
+    number INTEGER NOT NULL,
+    player UUID NOT NULL,
+    card JSONB,
+    effect JSONB,
+    dropped BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE (match, number)
+);
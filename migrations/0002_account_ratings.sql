@@ -0,0 +1,6 @@
+-- Рейтинг Glicko акаунтів, існуючі акаунти отримують рейтинг нового гравця (game::rating)
+ALTER TABLE accounts
+    ADD COLUMN IF NOT EXISTS rating DOUBLE PRECISION NOT NULL DEFAULT 1500,
+    ADD COLUMN IF NOT EXISTS rating_deviation DOUBLE PRECISION NOT NULL DEFAULT 350;
+
+CREATE INDEX IF NOT EXISTS accounts_rating_idx ON accounts (rating DESC, id); -- порядок таблиці лідерів
@@ -0,0 +1,4 @@
+-- Статистика серій ігор акаунтів, існуючі акаунти ще не грали серій
+ALTER TABLE accounts
+    ADD COLUMN IF NOT EXISTS series_played INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS series_won INTEGER NOT NULL DEFAULT 0;
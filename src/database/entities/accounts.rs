@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)] //Включити до цієї структури автоматичну реалізацію вказаних ознак
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)] //Включити до цієї структури автоматичну реалізацію вказаних ознак
 #[sea_orm(table_name = "accounts")] //Назва відповідної таблиці в базі даних, при компіляції додає до коду структуру ActiveModel,
 //що відтворює Model, але з типами даних оберненими в ActiveValue
 /*
@@ -33,6 +33,10 @@ pub struct Model { //Структура, що описує стовпці таб
     pub wins: i32,
     pub loses: i32,
     pub max_points: i16,
+    pub rating: f64,
+    pub rating_deviation: f64,
+    pub series_played: i32,
+    pub series_won: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,36 @@
+//! `SeaORM` Entity, схема в migrations/0001_matches.sql
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "match_moves")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub r#match: i64,
+    pub number: i32,
+    pub player: Uuid,
+    pub card: Option<Json>,
+    pub effect: Option<Json>,
+    pub dropped: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::matches::Entity",
+        from = "Column::Match",
+        to = "super::matches::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Matches,
+}
+
+impl Related<super::matches::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Matches.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,37 @@
+//! `SeaORM` Entity, схема в migrations/0001_matches.sql
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "matches")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(column_type = "Text")]
+    pub room: String,
+    pub seed: i64,
+    pub rules: Json,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::match_players::Entity")]
+    MatchPlayers,
+    #[sea_orm(has_many = "super::match_moves::Entity")]
+    MatchMoves,
+}
+
+impl Related<super::match_players::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MatchPlayers.def()
+    }
+}
+
+impl Related<super::match_moves::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MatchMoves.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
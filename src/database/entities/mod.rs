@@ -3,4 +3,7 @@
 pub mod prelude;
 
 pub mod accounts;
+pub mod match_moves;
+pub mod match_players;
+pub mod matches;
 pub mod sessions;
@@ -1,4 +1,7 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.14
 
 pub use super::accounts::Entity as Accounts;
+pub use super::match_moves::Entity as MatchMoves;
+pub use super::match_players::Entity as MatchPlayers;
+pub use super::matches::Entity as Matches;
 pub use super::sessions::Entity as Sessions;
@@ -0,0 +1,16 @@
+use sea_orm::{ ConnectionTrait, DatabaseConnection, DbErr };
+
+//Міграції схеми у порядку застосування, кожна з них ідемпотентна, тому вони виконуються при кожному запуску сервера
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("0001_matches", include_str!("../../migrations/0001_matches.sql")),
+    ("0002_account_ratings", include_str!("../../migrations/0002_account_ratings.sql")),
+    ("0003_account_series", include_str!("../../migrations/0003_account_series.sql")),
+];
+
+pub async fn run(db: &DatabaseConnection) -> Result<(), DbErr> { //Застосування усіх міграцій до бази даних
+    for (name, sql) in MIGRATIONS {
+        db.execute_unprepared(sql).await
+            .map_err(|e| DbErr::Migration(format!("{name}: {e}")))?;
+    }
+    Ok(())
+}
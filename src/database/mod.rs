@@ -1,2 +1,3 @@
 pub mod entities;
+pub mod migrations;
 pub mod queries;
\ No newline at end of file
@@ -1,6 +1,6 @@
 
-use sea_orm::{prelude::Uuid, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, Select, Set, TryInsert};
-use crate::database::entities::{ accounts, prelude::Accounts };
+use sea_orm::{prelude::Uuid, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Select, Set, TryInsert};
+use crate::{ database::entities::{ accounts, prelude::Accounts }, game::{ gameplay::player::Losers, rating::{ self, Rating }, rooms::series::SeriesOver } };
 
 pub fn by_uuid(uuid: Uuid) -> Select<Accounts> { //Підготувати SELECT запит за id гравця
     Accounts::find()
@@ -23,6 +23,8 @@ pub fn register(id: String, password: String, display_name: Option<String>) -> T
             login: Set(id.clone()),
             password: Set(password),
             display_name: Set(display_name.unwrap_or(id.clone())),
+            rating: Set(rating::DEFAULT_RATING),
+            rating_deviation: Set(rating::DEFAULT_DEVIATION),
             ..Default::default()
         },
     )
@@ -37,4 +39,54 @@ where F: FnOnce(&accounts::Model, &mut accounts::ActiveModel) { //та збер
     func(&model, &mut active_model); //виклик наданої функції і передача моделей їй
     active_model.save(db).await?; //збереження змін
     Ok(true)
-}
\ No newline at end of file
+}
+
+pub fn leaderboard(after: u64, limit: u64) -> Select<Accounts> { //SELECT запит акаунтів, відсортованих за рейтингом
+    Accounts::find()
+    .order_by_desc(accounts::Column::Rating)
+    .order_by_asc(accounts::Column::Id) //однаковий рейтинг сортується за id, щоб сторінки не перетиналися
+    .offset(after)
+    .limit(limit)
+}
+
+pub async fn save_game(db: &DatabaseConnection, players: &Losers) { //функція для збереження результатів гри у статистику акаунтів
+    let mut rated = Vec::new(); //акаунти гравців у порядку вибування, боти не мають акаунтів і не враховуються
+    for player in players.iter() {
+        if let Ok(Some(model)) = by_uuid(*player.id()).one(db).await {
+            rated.push(model);
+        }
+    }
+    if rated.len() >= 2 { //рейтинг оновлюється тільки після гри з кількома гравцями
+        let ratings: Vec<Rating> = rated.iter().map(|model| Rating { rating: model.rating, deviation: model.rating_deviation }).collect();
+        for (model, new) in rated.into_iter().zip(rating::update(&ratings)) {
+            let mut active_model = model.into_active_model();
+            active_model.rating = Set(new.rating);
+            active_model.rating_deviation = Set(new.deviation);
+            let _ = active_model.save(db).await;
+        }
+    }
+    for (index, player) in players.iter().enumerate() { //ітерація через усих гравців гри
+        let _ = update(db, *player.id(), |values, account| { //пошук їх акаунтів в БД та оновленя значень у замиканні
+            account.games_played = Set(values.games_played + 1); //збільшення кількості зіграних ігор на 1
+            if index < players.len() / 2 { //вважати виграшом якщо вибув з гри раніше за половину гравців
+                account.wins = Set(values.wins + 1); //збільшення кількості виграшів    
+            } else {
+                account.loses = Set(values.loses + 1); //збільшення кількості програшів
+            }
+            account.cards_had = Set(values.cards_had + *player.cards_had() as i64); //збільшення кількості карт які гравець тримав загалом
+            account.points = Set(values.points + *player.points() as i64); //збільшення кількості очків
+            account.max_points = Set(values.max_points.max(*player.points() as i16)) //порівняння значення найбільшої кількості очок за гру та їх назначення
+        }).await;
+    }
+}
+
+pub async fn save_series(db: &DatabaseConnection, series: &SeriesOver) { //функція для збереження результату серії ігор у статистику акаунтів
+    for standing in series.standings.iter() { //боти не мають акаунтів, тому для них оновлення нічого не змінює
+        let _ = update(db, standing.id, |values, account| {
+            account.series_played = Set(values.series_played + 1);
+            if standing.id == series.winner {
+                account.series_won = Set(values.series_won + 1);
+            }
+        }).await;
+    }
+}
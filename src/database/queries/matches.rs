@@ -0,0 +1,69 @@
+use sea_orm::{prelude::Uuid, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, LoaderTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use crate::{
+    database::entities::{ match_moves, match_players, matches, prelude::{ MatchMoves, MatchPlayers, Matches } },
+    game::gameplay::{ player::Losers, Game },
+};
+
+pub async fn create(db: &DatabaseConnection, room: String, game: &Game, players: &Losers) -> Result<i64, DbErr> { //Збереження завершеної гри
+    let result = Matches::insert(
+        matches::ActiveModel {
+            room: Set(room),
+            seed: Set(*game.seed() as i64), //u64 зберігається побітово у bigint
+            rules: Set(serde_json::to_value(game.rules()).unwrap_or_default()),
+            ..Default::default()
+        },
+    ).exec(db).await?;
+    let id = result.last_insert_id;
+    let participants = players.iter().enumerate().map(|(place, player)| { //місця гравців у порядку вибування
+        match_players::ActiveModel {
+            r#match: Set(id),
+            player: Set(*player.id()),
+            place: Set(place as i16),
+            points: Set(*player.points() as i64),
+            cards_had: Set(*player.cards_had() as i32),
+            ..Default::default()
+        }
+    });
+    MatchPlayers::insert_many(participants).on_empty_do_nothing().exec(db).await?;
+    let moves = game.moves().iter().enumerate().map(|(number, mv)| { //журнал ходів
+        match_moves::ActiveModel {
+            r#match: Set(id),
+            number: Set(number as i32),
+            player: Set(mv.player),
+            card: Set(mv.card.as_ref().and_then(|card| serde_json::to_value(card).ok())),
+            effect: Set(mv.effect.as_ref().and_then(|effect| serde_json::to_value(effect).ok())),
+            dropped: Set(mv.dropped),
+            ..Default::default()
+        }
+    });
+    MatchMoves::insert_many(moves).on_empty_do_nothing().exec(db).await?;
+    Ok(id)
+}
+
+pub async fn by_player(db: &DatabaseConnection, player: Uuid, after: u64, limit: u64) -> Result<Vec<(matches::Model, Vec<match_players::Model>)>, DbErr> { //Список ігор гравця, від нових до старих
+    let models = Matches::find()
+        .inner_join(MatchPlayers) //гравець бере участь у грі один раз, тому з'єднання не дублює рядки
+        .filter(match_players::Column::Player.eq(player))
+        .order_by_desc(matches::Column::Id)
+        .offset(after)
+        .limit(limit)
+        .all(db)
+        .await?;
+    let players = models.load_many(MatchPlayers, db).await?; //учасники кожної гри окремим запитом, щоб не порушити пагінацію
+    Ok(models.into_iter().zip(players).collect())
+}
+
+pub async fn by_id(db: &DatabaseConnection, id: i64) -> Result<Option<(matches::Model, Vec<match_players::Model>, Vec<match_moves::Model>)>, DbErr> { //Гра з учасниками та журналом ходів
+    let Some(model) = Matches::find_by_id(id).one(db).await? else { return Ok(None) };
+    let players = MatchPlayers::find()
+        .filter(match_players::Column::Match.eq(id))
+        .order_by_asc(match_players::Column::Place)
+        .all(db)
+        .await?;
+    let moves = MatchMoves::find()
+        .filter(match_moves::Column::Match.eq(id))
+        .order_by_asc(match_moves::Column::Number)
+        .all(db)
+        .await?;
+    Ok(Some((model, players, moves)))
+}
@@ -1,2 +1,3 @@
 pub mod accounts;
+pub mod matches;
 pub mod sessions;
\ No newline at end of file
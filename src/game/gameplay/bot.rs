@@ -0,0 +1,58 @@
+use rand::{ rngs::StdRng, seq::SliceRandom };
+use serde::{ Serialize, Deserialize };
+use super::card::{ Card, Effect };
+
+pub trait Strategy { //Ознака, що задає метод вибору ходу бота
+    //hand - карти бота, card - карта на столі
+    //повертає індекс карти, яку треба зіграти, або None щоб взяти карту з колоди
+    fn choose(&self, hand: &[Card], card: &Card, rng: &mut StdRng) -> Option<usize>;
+}
+
+fn legal_moves<'a>(hand: &'a [Card], card: &'a Card) -> impl Iterator<Item = (usize, &'a Card)> { //Ітератор карт, які можна зіграти
+    hand.iter().enumerate().filter(|(_index, own)| own.play(card.clone()).is_ok())
+}
+
+pub struct RandomStrategy; //Стратегія, що грає випадкову дозволену карту
+
+impl Strategy for RandomStrategy {
+    fn choose(&self, hand: &[Card], card: &Card, rng: &mut StdRng) -> Option<usize> {
+        let moves: Vec<usize> = legal_moves(hand, card).map(|(index, _)| index).collect();
+        moves.choose(rng).copied() //генератор гри використовується щоб ходи ботів можна було відтворити за зерном
+    }
+}
+
+pub struct GreedyStrategy; //Стратегія, що грає найсильнішу дозволену карту з урахуванням коефіцієнту елементів
+
+impl Strategy for GreedyStrategy {
+    fn choose(&self, hand: &[Card], card: &Card, _rng: &mut StdRng) -> Option<usize> {
+        legal_moves(hand, card)
+            .map(|(index, own)| {
+                let power = match own.effect() { //сила ефекту: Atk - його значення, Add - найсильніший ефект, інші - 1
+                    Effect::Atk(power) => *power as f32,
+                    Effect::Add(num) => 12.0 + *num as f32,
+                    _ => 1.0,
+                };
+                (index, power * own.element().coefficient(*card.element()))
+            })
+            .fold(None, |best: Option<(usize, f32)>, (index, score)| match best { //перша карта з найбільшою оцінкою
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((index, score)),
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Bot { //Список стратегій ботів
+    Random,
+    Greedy,
+}
+
+impl Bot {
+    pub fn strategy(&self) -> &'static dyn Strategy { //Повернути реалізацію стратегії
+        match self {
+            Bot::Random => &RandomStrategy,
+            Bot::Greedy => &GreedyStrategy,
+        }
+    }
+}
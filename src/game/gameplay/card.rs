@@ -2,9 +2,9 @@ use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
-use serde::Serialize;
+use serde::{ Serialize, Deserialize };
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Element {
     Water,
     Fire,
@@ -57,7 +57,7 @@ impl Distribution<Element> for Standard { //Релізація генерато
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Effect {
     Atk(u8),
     Flow,
@@ -76,8 +76,10 @@ impl Distribution<Effect> for Standard { //Генератор рандомних
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Card { //Структура, що описує карту
+    #[serde(default)] //карти, задані у правилах, не мають ідентифікатора
+    id: u32, //Унікальний в межах гри ідентифікатор, 0 - карта не з колоди
     element: Element,
     effect: Effect, 
 }
@@ -85,11 +87,29 @@ pub struct Card { //Структура, що описує карту
 impl Card { //Методи структури Card
     pub fn new(element: Element, effect: Effect) -> Self { //Конструктор
         Self {
+            id: 0,
             element,
             effect,
         }
     }
 
+    pub fn with_id(mut self, id: u32) -> Self { //Задання ідентифікатора
+        self.id = id;
+        self
+    }
+
+    pub fn id(&self) -> &u32 { //Геттер ідентифікатора
+        &self.id
+    }
+
+    pub fn element(&self) -> &Element { //Геттер елементу
+        &self.element
+    }
+
+    pub fn effect(&self) -> &Effect { //Геттер ефекту
+        &self.effect
+    }
+
     pub fn play(&self, card: Self) -> Result<Effect, ()> { //Метод для битви двох карт
         let coef = self.element.coefficient(card.element); //визначення коефіцієнту
         let other_power = match card.effect { //Визначення базової атаки попередньої карти
@@ -112,10 +132,11 @@ impl Card { //Методи структури Card
 }
 
 impl Distribution<Card> for Standard { //Генератор рандомних значень для Card
-    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> Card {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Card {
         Card {
-            element: rand::random(),
-            effect: rand::random(), 
+            id: 0,
+            element: rng.gen(), //значення беруться з наданого генератора, щоб гру можна було відтворити за зерном
+            effect: rng.gen(), 
         }
 
     }
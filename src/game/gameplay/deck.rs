@@ -0,0 +1,111 @@
+use rand::{ rngs::StdRng, seq::SliceRandom };
+use serde::{ser::SerializeStruct, Serialize};
+use super::{ card::{ Card, Effect, Element }, rules::DeckRules };
+
+const ELEMENTS: [Element; 6] = [ //Список елементів, для кожного з яких створюється однаковий набір карт
+    Element::Water,
+    Element::Fire,
+    Element::Wood,
+    Element::Earth,
+    Element::Air,
+    Element::Energy,
+];
+
+#[derive(Debug, Clone)]
+pub struct Deck { //Структура, що описує колоду гри
+    cards: Vec<Card>, //Карти, які залишилися в колоді
+    discard: Vec<Card>, //Зіграні карти, остання з них лежить на столі
+}
+
+impl Deck {
+    pub fn new(rules: &DeckRules, rng: &mut StdRng) -> Self { //Конструктор, створює перемішану колоду складу, заданого правилами
+        let mut cards = Vec::new();
+        for element in ELEMENTS { //для кожного елементу:
+            for _i in 0..rules.atk { //копії карт Atk кожної сили від 1 до 12
+                for power in 1..=12 {
+                    cards.push(Card::new(element, Effect::Atk(power)));
+                }
+            }
+            for _i in 0..rules.flow { //копії карт Flow
+                cards.push(Card::new(element, Effect::Flow));
+            }
+            for _i in 0..rules.stun { //копії карт Stun
+                cards.push(Card::new(element, Effect::Stun));
+            }
+            for _i in 0..rules.add { //копії карт Add кожної кількості від 1 до 4
+                for num in 1..=4 {
+                    cards.push(Card::new(element, Effect::Add(num)));
+                }
+            }
+        }
+        let mut cards: Vec<Card> = cards.into_iter().enumerate()
+            .map(|(index, card)| card.with_id(index as u32 + 1)) //кожна карта колоди отримує унікальний ідентифікатор
+            .collect();
+        cards.shuffle(rng); //перемішування колоди
+        Self { cards, discard: Vec::new() }
+    }
+
+    pub fn draw(&mut self, rng: &mut StdRng) -> Option<Card> { //Метод взяття карти з колоди
+        if self.cards.is_empty() && self.discard.len() > 1 { //якщо колода закінчилася, то перемішати відбій у нову колоду
+            let top = self.discard.pop(); //карта на столі залишається у відбої
+            self.cards.append(&mut self.discard);
+            self.cards.shuffle(rng);
+            self.discard.extend(top);
+        }
+        self.cards.pop() //None якщо усі карти на руках у гравців
+    }
+
+    pub fn discard(&mut self, card: Card) { //Метод переміщення зіграної карти у відбій
+        self.discard.push(card);
+    }
+}
+
+impl Serialize for Deck { //Власноруч реалізована серіалізація, замість карт серіалізується тільки їх кількість
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer {
+        let mut state = serializer.serialize_struct("Deck", 2)?;
+        state.serialize_field("cards", &self.cards.len())?;
+        state.serialize_field("discard", &self.discard.len())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn small_deck(rng: &mut StdRng) -> Deck { //Колода з однієї карти Flow кожного елементу
+        Deck::new(&DeckRules { atk: 0, flow: 1, stun: 0, add: 0 }, rng)
+    }
+
+    #[test]
+    fn draw_reshuffles_discard_except_top_card() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut deck = small_deck(&mut rng);
+        let hand: Vec<Card> = (0..6).map(|_| deck.draw(&mut rng).unwrap()).collect();
+        assert!(deck.draw(&mut rng).is_none()); //усі карти на руках, відбій порожній
+        for card in &hand[..3] {
+            deck.discard(card.clone());
+        }
+        let top = *hand[2].id();
+        let mut drawn: Vec<u32> = (0..2).map(|_| *deck.draw(&mut rng).unwrap().id()).collect();
+        drawn.sort();
+        let mut expected = vec![*hand[0].id(), *hand[1].id()];
+        expected.sort();
+        assert_eq!(drawn, expected); //у нову колоду потрапили усі карти відбою, крім карти на столі
+        assert_eq!(deck.discard.iter().map(|card| *card.id()).collect::<Vec<_>>(), vec![top]);
+        assert!(deck.draw(&mut rng).is_none());
+    }
+
+    #[test]
+    fn draw_keeps_single_discarded_card() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut deck = small_deck(&mut rng);
+        let hand: Vec<Card> = (0..6).map(|_| deck.draw(&mut rng).unwrap()).collect();
+        deck.discard(hand[0].clone());
+        assert!(deck.draw(&mut rng).is_none()); //єдина карта відбою лежить на столі і не перемішується
+        assert_eq!(deck.discard.len(), 1);
+    }
+}
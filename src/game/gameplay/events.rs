@@ -0,0 +1,41 @@
+use sea_orm::prelude::Uuid;
+use serde::Serialize;
+use super::card::{ Card, Effect };
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CardPlayed { //Подія зіграної карти
+    pub player: Uuid, //гравець, що зіграв карту
+    pub card: Card, //зіграна карта
+    pub effect: Effect, //ефект карти
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CardsDrawn { //Подія взяття карт з колоди, самі карти бачить тільки гравець у HandDelta
+    pub player: Uuid, //гравець, що взяв карти
+    pub count: usize, //кількість взятих карт
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TurnChanged { //Подія передачі ходу
+    pub player: Uuid, //гравець, що ходить
+    pub turn_id: u64, //номер ходу
+    pub deadline: Option<i64>, //час закінчення ходу у мілісекундах Unix
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PlayerFinished { //Подія вибуття гравця з гри
+    pub player: Uuid,
+    pub dropped: bool, //true - гравця видалено за пропуск ходів, false - гравець позбувся усіх карт
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct HandDelta { //Подія зміни карт гравця, надсилається тільки йому
+    pub added: Vec<Card>, //нові карти
+    pub removed: Vec<u32>, //ідентифікатори карт, що більше не в руці
+}
+
+impl HandDelta {
+    pub fn is_empty(&self) -> bool { //Чи змінилася рука гравця
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
@@ -1,13 +1,23 @@
+pub mod bot;
 pub mod card;
+pub mod deck;
+pub mod events;
 pub mod player;
+pub mod rules;
 
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
+use rand::{ rngs::StdRng, SeedableRng };
 use sea_orm::prelude::Uuid;
 use serde::Serialize;
-use crate::{game::rooms, gateway::payloads::Payload};
-use card::{ Card, Element, Effect };
+use crate::{game::rooms::{self, spectator::{self, Spectators}}, gateway::payloads::{ Event, Payload }, runtime_storage::DataTable};
+use card::{ Card, Effect };
+use deck::Deck;
+use events::*;
+use rules::RuleSet;
 use player::*;
-use tokio::sync::broadcast::Sender;
+use tokio::sync::{ RwLock, broadcast::Sender };
+
+const BOT_SEED_SALT: u64 = 0x9E37_79B9_7F4A_7C15; //Зсув зерна для генератора ботів, щоб їх вибір не змінював порядок карт у колоді
 
 pub enum Ok { //Список усіх можливих Ok значень
     Ok,
@@ -18,6 +28,7 @@ pub enum Ok { //Список усіх можливих Ok значень
 pub enum Error { //Список усіх можливих помилок
     NotEnoughPlayers,
     CardNotFound,
+    CardNotInHand,
     PlayerNotFound,
     WrongTurn,
     WrongCard,
@@ -25,7 +36,7 @@ pub enum Error { //Список усіх можливих помилок
 }
 
 #[derive(Debug, Serialize, Clone)]
-enum Direction { //Список напрямків ходів
+pub enum Direction { //Список напрямків ходів
     Next,
     Previous,
 }
@@ -40,40 +51,127 @@ impl Direction {
     }
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct Move { //Структура, що описує один виклик методу play або видалення гравця з гри
+    pub player: Uuid, //гравець, що ходив
+    pub card: Option<Card>, //зіграна карта, None - карта взята з колоди
+    pub effect: Option<Effect>, //ефект зіграної карти
+    pub dropped: bool, //гравця видалено з гри через пропущені ходи або вигнання, карта не бралася
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct Game { //Структура, що описує гру
     card: Card, //Попередня зіграна карта
     players: Vec<Player>, //Вектор гравців
     turn: usize, //індекс гравця, що ходить
     direction: Direction, //напрямок ходів
+    deadline: Option<i64>, //час закінчення ходу у мілісекундах Unix, None - без обмеження
+    deck: Deck, //колода та відбій, серіалізується тільки кількість карт
     #[serde(skip)] //пропуск серіалізації
     losers: Vec<Loser>, //вектор гравців, що вибули
     #[serde(skip)]
+    dropped: Vec<Loser>, //вектор гравців, яких було видалено за пропуск ходів
+    #[serde(skip)]
+    moves: Vec<Move>, //журнал ходів гри
+    #[serde(skip)]
+    turn_id: u64, //номер ходу, за яким таймер визначає чи хід ще не змінився
+    #[serde(skip)]
     gameover: bool, //чи закінчилася ця гра
+    #[serde(skip)]
+    rules: RuleSet, //правила гри
+    #[serde(skip)]
+    rng: StdRng, //генератор карт, ініціалізований зерном гри, використовується тільки для роздачі та взяття карт
+    #[serde(skip)]
+    bot_rng: StdRng, //окремий генератор для вибору карт ботами
+    #[serde(skip)]
+    seed: u64, //зерно генератора
+    #[serde(rename = "seed")]
+    revealed_seed: Option<u64>, //зерно, яке розкривається тільки після закінчення гри
+    #[serde(skip)]
+    spectators: Spectators, //глядачі кімнати, отримують усі події гри окрім карт гравців
+    #[serde(skip)]
+    spectator_delay: u64, //затримка подій для глядачів у секундах
+    #[serde(skip)]
+    events: Vec<Event>, //події ходу, що ще не були оголошені
 }
 
 impl Game {
-    pub fn new(players: HashSet<rooms::player::Player>) -> Result<Self, Error> { //Конструктор
+    pub fn new(players: HashSet<rooms::player::Player>, rules: RuleSet, seed: Option<u64>) -> Result<Self, Error> { //Конструктор
+        let seed = seed.unwrap_or_else(rand::random); //якщо зерно не надане, то згенерувати нове
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut ready: Vec<rooms::player::Player> = players.into_iter().filter(|player| player.is_ready).collect(); //беруться тільки готові гравці
+        ready.sort_by_key(|player| player.id); //порядок набору випадковий, тому гравці сортуються для відтворюваності роздачі
+        let mut deck = Deck::new(&rules.deck, &mut rng); //створення перемішаної колоди
         let mut players_new = Vec::new(); //створення нового вектору
-        for player in players.into_iter().filter(|player| player.is_ready == true) { //Перетворення набору гравців кімнати у вектор гравців гри,
-            //беруться тільки готові гравці
-            players_new.push(player.into()) //перетворення додавання гравця у вектор
+        for player in ready { //Перетворення гравців кімнати у вектор гравців гри
+            let cards = (0..rules.hand_size).map(|_| deck.draw(&mut rng)).collect::<Option<Vec<Card>>>()
+                .ok_or(Error::NoCardsLeft)?; //роздача карт з колоди, помилка якщо карт не вистачає
+            players_new.push(Player::new(player, cards))
         };
-        if players_new.len() < 2 { return Err(Error::NotEnoughPlayers) } //якщо гравців менше за 2, то гра не може бути розпочатою. Повернення помилки
-        Ok(
-            Self {
-                card: Card::new(Element::Energy, Effect::Flow),
-                players: players_new,
-                turn: 0,
-                direction: Direction::Next,
-                losers: Vec::new(),
-                gameover: false,
-            }
-        )
+        if players_new.len() < rules.min_players { return Err(Error::NotEnoughPlayers) } //якщо гравців менше за мінімальну кількість, то гра не може бути розпочатою. Повернення помилки
+        let mut game = Self {
+            card: rules.starting_card.clone(),
+            players: players_new,
+            turn: 0,
+            direction: Direction::Next,
+            deadline: None,
+            deck,
+            losers: Vec::new(),
+            dropped: Vec::new(),
+            moves: Vec::new(),
+            turn_id: 0,
+            gameover: false,
+            rules,
+            rng,
+            bot_rng: StdRng::seed_from_u64(seed ^ BOT_SEED_SALT),
+            seed,
+            revealed_seed: None,
+            spectators: Arc::new(RwLock::new(DataTable::new())),
+            spectator_delay: 0,
+            events: Vec::new(),
+        };
+        game.start_turn();
+        Ok(game)
+    }
+
+    fn start_turn(&mut self) { //Метод початку нового ходу, оновлює номер ходу та час його закінчення
+        self.turn_id += 1;
+        self.deadline = self.rules.turn_timeout
+            .map(|timeout| chrono::Utc::now().timestamp_millis() + timeout as i64 * 1000);
+    }
+
+    pub fn rules(&self) -> &RuleSet { //Геттер правил гри
+        &self.rules
+    }
+
+    pub fn moves(&self) -> &Vec<Move> { //Геттер журналу ходів
+        &self.moves
+    }
+
+    pub fn turn_id(&self) -> &u64 { //Геттер номеру ходу
+        &self.turn_id
+    }
+
+    pub fn turn_timeout(&self) -> Option<u64> { //Геттер часу на хід з правил гри
+        self.rules.turn_timeout
+    }
+
+    pub fn is_bot_turn(&self) -> bool { //Чи ходить зараз бот
+        !self.gameover && self.players.get(self.turn).is_some_and(|player| player.bot().is_some())
+    }
+
+    pub fn seed(&self) -> &u64 { //Геттер зерна
+        &self.seed
+    }
+
+    pub fn set_spectators(&mut self, spectators: Spectators, delay: u64) { //Сеттер глядачів гри
+        self.spectators = spectators;
+        self.spectator_delay = delay;
     }
 
 
-    pub fn announce(&self, content: String ) { //Оголошення повідомлення
+    pub fn announce(&self, content: Event ) { //Оголошення повідомлення
+        spectator::announce(&self.spectators, content.clone(), self.spectator_delay); //глядачам із затримкою
         let game = self.clone();
         tokio::spawn(async move {
             for player in game.players {
@@ -82,30 +180,55 @@ impl Game {
         });
     }
 
-    pub fn player_update_sender(&mut self, player_id: Uuid, sender: Sender<String>) -> bool { //Відновлення сесії гравця
+    pub fn player_update_sender(&mut self, player_id: Uuid, sender: Sender<Event>) -> bool { //Відновлення сесії гравця
         let Ok(index) = self.get_player_index(player_id) else { return false };
         let game = self.clone();
         if let Some(player) = self.players.get_mut(index) {
             player.sender = sender.clone();
-            let _ = sender.send(Payload::GameStarted(game.clone()).to_json_string());
-            let _ = sender.send(Payload::GamePlayerCards(player.cards().clone()).to_json_string());
+            let _ = sender.send(Payload::GameStarted(Box::new(game.clone())).to_event());
+            let _ = sender.send(Payload::GamePlayerCards(player.cards().clone()).to_event());
             true
         } else { false }
     }
 
-    pub fn announce_turn(&self, new_turn: bool) { //Оголошення нового ходу
-        let game = self.clone();
+    pub fn announce_hands(&self) { //Оголошення наявних карт кожному гравцю
+        for player in self.players.iter() {
+            let _ = player.sender.send(Payload::GamePlayerCards(player.cards().clone()).to_event());
+        }
+    }
+
+    fn push_event(&mut self, event: Payload) { //Додавання події до наступного оголошення
+        self.events.push(event.to_event());
+    }
+
+    fn announce_events(&mut self) { //Оголошення накопичених подій ходу та змін карт гравців
+        let events: Vec<Event> = self.events.drain(..).collect();
+        for event in events.iter() { //глядачі отримують тільки публічні події, без карт гравців
+            spectator::announce(&self.spectators, event.clone(), self.spectator_delay);
+        }
+        let mut messages = Vec::new(); //карти гравців, що вибули, більше не оголошуються
+        for player in self.players.iter_mut() {
+            let delta = player.take_hand_delta();
+            let mut content = events.clone();
+            if !delta.is_empty() { content.push(Payload::HandDelta(delta).to_event()) }
+            messages.push((player.sender.clone(), content));
+        }
         tokio::spawn(async move { //Створення нового потоку, щоб не тормозити гру
-            for player in game.players.iter() {
-                if new_turn == true { //Якщо не нова гра, то надіслати інформацію про новий хід
-                    let _ = player.sender.send(Payload::GameNewTurn(game.clone()).to_json_string());
+            for (sender, content) in messages {
+                for text in content {
+                    let _ = sender.send(text);
                 }
-                let cards = player.cards().clone(); //Доставання карт гравця
-                let _ = player.sender.send(Payload::GamePlayerCards(cards).to_json_string()); //надсилання інформації про наявні карти відповідному гравцю
             }
         });
     }
 
+    fn next_turn(&mut self) { //Початок нового ходу та оголошення подій попереднього
+        self.start_turn();
+        let Some(player) = self.players.get(self.turn).map(|player| *player.id()) else { return };
+        self.push_event(Payload::TurnChanged(TurnChanged { player, turn_id: self.turn_id, deadline: self.deadline }));
+        self.announce_events();
+    }
+
     pub fn get_player_index(&mut self, player_id: Uuid) -> Result<usize, Error> { //метод для знаходження індексу гравця у векторі за його id
         self.players.iter().enumerate() //пройтися по вектору та прономерувати
             .find(|(_index, player)| { *player.id() == player_id }) //Знайти гравця з вказаним id
@@ -117,17 +240,20 @@ impl Game {
         &self.gameover
     }
 
-    pub fn play(&mut self, player_id: Uuid, card_id: Option<usize>) -> Result<Ok, Error> { //Метод для грання
+    pub fn play(&mut self, player_id: Uuid, card_id: Option<u32>) -> Result<Ok, Error> { //Метод для грання
         let mut step = 1; //Наступний хід буде належати гравцю з індексом теперішнього ходу + або - цієї змінної в залежності напрямку
         let index = self.get_player_index(player_id)?; //доставання індексу гравця, що грає
         if index != self.turn { return Err(Error::WrongTurn) } //якщо не його хід то повернути помилку
         let player = &mut self.players[index]; //звертання до об'єкту гравця за індексом
         let mut cards_to_pick = 0; //кількість карт які наступний гравець повинен буде взяти
-        if let Some(card_id) = card_id { //якщо вказаний індекс карти
-            let card = player.get_card(card_id).ok_or(Error::CardNotFound)?; //доставання посилання на карту гравця за вказаним індексом
-            let effect = card.play(self.card.clone()).map_err(|_| Error::WrongCard)?; //якщо індекс невірний то повернути помилку
+        let mut finished = false; //чи вибув гравець, зігравши останню карту
+        if let Some(card_id) = card_id { //якщо вказаний ідентифікатор карти
+            let (card_index, card) = player.get_card(card_id).ok_or(Error::CardNotInHand)?; //доставання карти гравця за ідентифікатором, помилка якщо її немає в руці
+            let effect = card.play(self.card.clone()).map_err(|_| Error::WrongCard)?; //якщо карту не можна зіграти то повернути помилку
             self.card = card.clone(); //клонування об'єкту
-            player.remove_card(card_id); //Видалення цієї карти з вектору карт гравця
+            self.deck.discard(player.remove_card(card_index)); //Видалення цієї карти з вектору карт гравця та переміщення у відбій
+            let played = CardPlayed { player: player_id, card: self.card.clone(), effect };
+            let direction_changed = matches!(effect, Effect::Flow);
             match effect { //В залежності від ефекту:
                 Effect::Stun => { step += 1 }, //Stun - Пропустити хід наступному гравцю
                 Effect::Flow => { self.direction.switch(); }, //Flow - змінити напрямок
@@ -136,45 +262,173 @@ impl Game {
                 },
                 _ => {}, //Будь-який інший - нічого
             }
-            if player.cards().len() == 0 { //вибути з гри якщо не залишилося карт
+            self.moves.push(Move { player: player_id, card: Some(self.card.clone()), effect: Some(effect), dropped: false }); //запис ходу в журнал
+            finished = player.cards().len() == 0;
+            self.push_event(Payload::CardPlayed(played));
+            if direction_changed {
+                self.push_event(Payload::DirectionChanged(self.direction.clone()));
+            }
+            if finished { //вибути з гри якщо не залишилося карт
                 self.losers.push(self.players[index].clone().into()); 
                 self.players.remove(index);
+                self.push_event(Payload::PlayerFinished(PlayerFinished { player: player_id, dropped: false }));
             }
         } else {
-            let _ = self.pick_card(index); //якщо не вказаний індекс карти, то взяти з колоди
+            self.draw_cards(index, 1); //якщо не вказаний ідентифікатор карти, то взяти з колоди
+            self.moves.push(Move { player: player_id, card: None, effect: None, dropped: false });
+        }
+        if let Some(player) = self.players.get_mut(index).filter(|_| !finished) { //гравець зробив хід сам, скинути лічильник пропущених ходів
+            player.timeouts = 0;
         }
         let turn = match self.direction { //визначення хто ходить наступним за напрямком
-            Direction::Next => self.turn as isize + step,
+            //якщо гравець вибув, наступний за ним вже зайняв його індекс
+            Direction::Next => self.turn as isize + step - finished as isize,
             Direction::Previous => self.turn as isize - step,
         };
-        //Зациклення результату таким чином, щоб він завжди був в межах довжини вектора
-        if turn < 0 {
-            self.turn = (turn + self.players.len() as isize) as usize;
-        } else if turn >= self.players.len() as isize {
-            self.turn = (turn - self.players.len() as isize) as usize;
-        } else {
-            self.turn = turn as usize;
-        }
-        for _i in 0..cards_to_pick {
-            let _ = self.pick_card(self.turn);
+        if !self.players.is_empty() { //Зациклення результату таким чином, щоб він завжди був в межах довжини вектора після видалення
+            self.turn = turn.rem_euclid(self.players.len() as isize) as usize;
         }
+        self.draw_cards(self.turn, cards_to_pick as usize);
         if self.players.len() <= 1 { //Якщо залишився тільки один гравець
-            //то завершити гру 
-            if let Some(winner) = self.players.pop() {
-                self.losers.push(winner.into()); //додаючи останнього гравця в список вибувших
-            }
-            self.gameover = true;
-            return Ok(Ok::GameOver(self.losers.clone().into())) //та повернути результат, що вказує на завершення
+            return Ok(self.finish()) //то завершити гру та повернути результат, що вказує на завершення
         }
         //Оголошення нового ходу
-        self.announce_turn(true);
+        self.next_turn();
         Ok(Ok::Ok)
     }
 
+    fn finish(&mut self) -> Ok { //Метод завершення гри
+        self.announce_events(); //оголошення останнього ходу перед результатами
+        if let Some(winner) = self.players.pop() {
+            self.losers.push(winner.into()); //додаючи останнього гравця в список вибувших
+        }
+        self.losers.extend(self.dropped.drain(..).rev()); //видалені гравці займають останні місця, першим видалений - останнє
+        self.gameover = true;
+        self.deadline = None;
+        self.revealed_seed = Some(self.seed); //розкрити зерно для відтворення гри
+        Ok::GameOver(Losers::new(self.losers.clone(), &self.rules.scoring))
+    }
+
+    pub fn abandon(&mut self) { //Метод зупинки гри без результатів, наприклад при закритті кімнати
+        self.gameover = true;
+        self.deadline = None;
+    }
+
+    pub fn play_bot(&mut self, turn_id: u64) -> Result<Ok, Error> { //Метод ходу бота
+        if self.gameover || turn_id != self.turn_id { return Err(Error::WrongTurn) } //хід вже змінився
+        let player = self.players.get(self.turn).ok_or(Error::PlayerNotFound)?;
+        let Some(bot) = *player.bot() else { return Err(Error::WrongTurn) }; //гравець не є ботом
+        let id = *player.id();
+        let card_id = bot.strategy().choose(player.cards(), &self.card, &mut self.bot_rng) //вибір карти стратегією бота
+            .map(|index| *player.cards()[index].id());
+        self.play(id, card_id)
+    }
+
+    pub fn timeout(&mut self, turn_id: u64) -> Result<Ok, Error> { //Метод обробки закінчення часу на хід
+        if self.gameover || turn_id != self.turn_id { return Err(Error::WrongTurn) } //хід вже змінився, нічого не робити
+        let index = self.turn;
+        let player = self.players.get(index).ok_or(Error::PlayerNotFound)?;
+        let timeouts = player.timeouts + 1;
+        if timeouts < self.rules.max_timeouts { //якщо гравець ще не перевищив кількість пропусків
+            let result = self.play(*player.id(), None)?; //то взяти за нього карту та передати хід
+            if let Some(player) = self.players.get_mut(index) { player.timeouts = timeouts } //хід взятий не гравцем, тому лічильник не скидається
+            return Ok(result)
+        }
+        Ok(self.drop_player(index)) //інакше видалити гравця з гри
+    }
+
+    pub fn kick(&mut self, player_id: Uuid) -> Ok { //Метод видалення гравця, якого вигнали з кімнати
+        if self.gameover { return Ok::Ok }
+        let Ok(index) = self.get_player_index(player_id) else { return Ok::Ok }; //гравець не бере участі в грі
+        self.drop_player(index)
+    }
+
+    fn drop_player(&mut self, index: usize) -> Ok { //Метод видалення гравця з гри, він займає останнє з вільних місць
+        let player = self.players.remove(index);
+        self.moves.push(Move { player: *player.id(), card: None, effect: None, dropped: true }); //видалення змінює черговість, тому записується в журнал для відтворення гри
+        self.push_event(Payload::PlayerFinished(PlayerFinished { player: *player.id(), dropped: true }));
+        self.dropped.push(player.into());
+        if self.players.len() <= 1 { return self.finish() }
+        if index == self.turn { //якщо видалено гравця, що ходить
+            self.turn = match self.direction { //наступний за напрямком гравець зайняв індекс видаленого або стоїть перед ним
+                Direction::Next => index % self.players.len(),
+                Direction::Previous => (index + self.players.len() - 1) % self.players.len(),
+            };
+            self.next_turn();
+        } else { //інакше хід не змінюється, тільки зсувається індекс
+            if index < self.turn { self.turn -= 1 }
+            self.announce_events();
+        }
+        Ok::Ok
+    }
+
+    fn draw_cards(&mut self, player_index: usize, count: usize) { //Взяття кількох карт з оголошенням їхньої кількості
+        let drawn = (0..count).take_while(|_| self.pick_card(player_index).is_ok()).count();
+        if drawn == 0 { return }
+        let Some(player) = self.players.get(player_index).map(|player| *player.id()) else { return };
+        self.push_event(Payload::CardsDrawn(CardsDrawn { player, count: drawn }));
+    }
+
     pub fn pick_card(&mut self, player_index: usize) -> Result<(), Error> { //Метод взяття карти з колоди
         //взяття гравця за індексом та повернення помилки якщо такого гравця немає
         let player = self.players.get_mut(player_index).ok_or(Error::PlayerNotFound)?;
-        player.add_card(rand::random()); //Виклик методу додавання карти гравцю
+        let card = self.deck.draw(&mut self.rng).ok_or(Error::NoCardsLeft)?; //взяття карти з колоди, помилка якщо карт не залишилося
+        player.add_card(card); //Виклик методу додавання карти гравцю
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use card::Element;
+    use bot::Bot;
+
+    fn game(players: usize) -> Game { //Гра ботів з однією картою в руці, щоб гравець вибував першим же ходом
+        let rules = RuleSet { hand_size: 1, ..RuleSet::default() };
+        let players = (0..players).map(|_| rooms::player::Player::new_bot(Bot::Random)).collect();
+        let mut game = Game::new(players, rules, Some(1)).unwrap();
+        game.card = Card::new(Element::Energy, Effect::Atk(1)); //будь-яка Atk карта Energy б'є цю карту
+        game
+    }
+
+    fn finish(game: &mut Game, index: usize) -> Uuid { //Хід гравця з індексом index його останньою картою
+        let player = &mut game.players[index];
+        player.remove_card(0);
+        player.add_card(Card::new(Element::Energy, Effect::Atk(12)).with_id(1000));
+        let id = *player.id();
+        game.turn = index;
+        assert!(matches!(game.play(id, Some(1000)), Ok(Ok::Ok)));
+        assert!(game.players.iter().all(|player| *player.id() != id), "player did not finish");
+        id
+    }
+
+    fn ids(game: &Game) -> Vec<Uuid> {
+        game.players.iter().map(|player| *player.id()).collect()
+    }
+
+    #[tokio::test]
+    async fn turn_passes_to_next_player_when_last_index_finishes() {
+        let mut game = game(3);
+        let before = ids(&game);
+        finish(&mut game, 2);
+        assert_eq!(*game.players[game.turn].id(), before[0]); //після останнього гравця хід переходить до першого
+    }
+
+    #[tokio::test]
+    async fn turn_passes_to_player_that_took_the_index() {
+        let mut game = game(3);
+        let before = ids(&game);
+        finish(&mut game, 0);
+        assert_eq!(*game.players[game.turn].id(), before[1]); //наступний гравець зайняв індекс того, хто вибув
+    }
+
+    #[tokio::test]
+    async fn turn_wraps_backwards_when_first_index_finishes() {
+        let mut game = game(3);
+        let before = ids(&game);
+        game.direction = Direction::Previous;
+        finish(&mut game, 0);
+        assert_eq!(*game.players[game.turn].id(), before[2]);
+    }
+}
@@ -2,52 +2,63 @@ use sea_orm::prelude::Uuid;
 use serde::{ser::SerializeStruct, Serialize};
 use std::{ borrow::Borrow, hash::Hash, ops::Deref };
 use tokio::sync::broadcast::Sender;
-use super::card::Card;
-use crate::game::rooms;
+use super::{ bot::Bot, card::Card, events::HandDelta, rules::Scoring };
+use crate::{ game::rooms, gateway::payloads::Event };
 
 #[derive(Debug, Clone)]
 pub struct Player { //Структура, яка описує гравця гри
     id: Uuid, //Ідентифікатор
-    pub sender: Sender<String>, //Надсилач, на який підписується веб-сокет та по якому відправляються усі повідомлення
+    pub sender: Sender<Event>, //Надсилач, на який підписується веб-сокет та по якому відправляються усі повідомлення
     cards: Vec<Card>, //Вектор карт
     cards_count: u16, //Список карт які гравець тримав загалом
+    pub timeouts: u8, //Кількість пропущених поспіль ходів
+    bot: Option<Bot>, //Стратегія, якщо гравець є ботом
+    hand_delta: HandDelta, //Зміни карт з останнього оголошення
 }
 
 impl Player {
+    pub fn new(value: rooms::player::Player, cards: Vec<Card>) -> Self { //Конструктор гравця гри з гравця кімнати та роздачі карт
+        Self {
+            id: value.id,
+            sender: value.sender,
+            cards,
+            cards_count: 0,
+            timeouts: 0,
+            bot: value.bot,
+            hand_delta: HandDelta::default(),
+        }
+    }
+
     pub fn id(&self) -> &Uuid { //Геттер ідентифікатора
         &self.id
     }
 
     pub fn add_card(&mut self, card: Card) { //Метод додавання карти
+        self.hand_delta.added.push(card.clone());
         self.cards.push(card);
         self.cards_count += 1;
     }
 
     pub fn remove_card(&mut self, index: usize) -> Card { //Метод видалення карти
-        self.cards.remove(index)
+        let card = self.cards.remove(index);
+        self.hand_delta.removed.push(*card.id());
+        card
+    }
+
+    pub fn take_hand_delta(&mut self) -> HandDelta { //Забрати накопичені зміни карт для оголошення
+        std::mem::take(&mut self.hand_delta)
     }
 
-    pub fn get_card(&self, index: usize) -> Option<&Card> { //Метод взяття карти
-        self.cards.get(index)
+    pub fn get_card(&self, id: u32) -> Option<(usize, &Card)> { //Метод пошуку карти за ідентифікатором, повертає її індекс та посилання
+        self.cards.iter().enumerate().find(|(_index, card)| *card.id() == id)
     }
 
     pub fn cards(&self) -> &Vec<Card> { //Геттер вектора карт
         &self.cards
     }
-}
 
-impl From<rooms::player::Player> for Player { //Ознака яка реалізує перетворення гравця кімнати у гравця гри
-    fn from(value: rooms::player::Player) -> Self {
-        let mut cards: Vec<Card> = Vec::new();
-        for _i in 0..8 { //додавання восьми карт
-            cards.push(rand::random());
-        }
-        Self {
-            id: value.id,
-            sender: value.sender,
-            cards,
-            cards_count: 0,
-        }
+    pub fn bot(&self) -> &Option<Bot> { //Геттер стратегії бота
+        &self.bot
     }
 }
 
@@ -74,9 +85,10 @@ impl Serialize for Player { //Власноруч реалізована сері
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: serde::Serializer {
-        let mut state = serializer.serialize_struct("Player", 2)?;
+        let mut state = serializer.serialize_struct("Player", 3)?;
         state.serialize_field("id", &self.id)?;
         state.serialize_field("cards", &self.cards.len())?;
+        state.serialize_field("bot", &self.bot)?;
         state.end()
     }
 }
@@ -115,14 +127,14 @@ impl From<Player> for Loser { //Перетворювач гравця в гра
 #[derive(Debug, Clone, Serialize)]
 pub struct Losers(Vec<Loser>); //Обертання вектора в нову структуру для додавання власних методів
 
-impl From<Vec<Loser>> for Losers {
-    fn from(value: Vec<Loser>) -> Self {
-        //Цей перетворювач обчислює кількість очків гравців всередині вектора
+impl Losers {
+    pub fn new(value: Vec<Loser>, scoring: &Scoring) -> Self {
+        //Цей конструктор обчислює кількість очків гравців всередині вектора за наданою формулою
         let len = value.len();
         let mut losers = Vec::new();
         for (i, loser) in value.iter().enumerate() {
             let mut loser = loser.clone();
-            loser.points = ((len*10)*(len-i)/len) as u64;
+            loser.points = scoring.points(i, len);
             losers.push(loser);
         }
         Self(losers)
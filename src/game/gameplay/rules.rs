@@ -0,0 +1,82 @@
+use serde::{ Serialize, Deserialize };
+use super::card::{ Card, Effect, Element };
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Scoring { //Список формул обчислення очків
+    Placement, //очки за місцем: гравець, що вибув першим, отримує найбільше
+    WinnerTakesAll, //усі очки отримує тільки гравець, що вибув першим
+}
+
+impl Scoring {
+    //place - місце гравця починаючи з 0, len - кількість гравців у грі
+    pub fn points(&self, place: usize, len: usize) -> u64 { //Метод обчислення очків гравця
+        match self {
+            Scoring::Placement => ((len*10)*(len-place)/len) as u64,
+            Scoring::WinnerTakesAll => if place == 0 { (len*10) as u64 } else { 0 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] //відсутні поля беруться зі значень за замовченням
+pub struct DeckRules { //Структура, що описує склад колоди, кількість копій вказується для кожного елементу
+    pub atk: u8, //копій карт Atk кожної сили від 1 до 12
+    pub flow: u8, //копій карт Flow
+    pub stun: u8, //копій карт Stun
+    pub add: u8, //копій карт Add кожної кількості від 1 до 4
+}
+
+impl Default for DeckRules {
+    fn default() -> Self { //Значення за замовченням
+        Self { atk: 2, flow: 2, stun: 2, add: 1 }
+    }
+}
+
+impl DeckRules {
+    pub fn size(&self) -> usize { //Кількість карт у колоді такого складу
+        (self.atk as usize * 12 + self.flow as usize + self.stun as usize + self.add as usize * 4) * 6
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet { //Структура, що описує правила гри кімнати
+    pub hand_size: usize, //Кількість карт, що роздаються кожному гравцю
+    pub min_players: usize, //Мінімальна кількість готових гравців для початку гри
+    pub starting_card: Card, //Карта, яка лежить на столі на початку гри
+    pub deck: DeckRules, //Склад колоди
+    pub scoring: Scoring, //Формула обчислення очків
+    pub turn_timeout: Option<u64>, //Час на хід у секундах, None - без обмеження
+    pub max_timeouts: u8, //Кількість пропущених поспіль ходів, після якої гравець вибуває з гри
+}
+
+impl Default for RuleSet {
+    fn default() -> Self { //Значення за замовченням
+        Self {
+            hand_size: 8,
+            min_players: 2,
+            starting_card: Card::new(Element::Energy, Effect::Flow),
+            deck: DeckRules::default(),
+            scoring: Scoring::Placement,
+            turn_timeout: Some(60),
+            max_timeouts: 3,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn validate(&self) -> Result<(), &'static str> { //Перевірка правил на коректність
+        if self.hand_size == 0 || self.hand_size > 32 { return Err("hand_size must be between 1 and 32") }
+        if self.min_players < 2 { return Err("min_players can't be lower than 2") }
+        if self.deck.size() < self.hand_size * self.min_players { return Err("deck is too small to deal hands to min_players") }
+        if let Some(timeout) = self.turn_timeout {
+            if !(5..=600).contains(&timeout) { return Err("turn_timeout must be between 5 and 600 seconds") }
+        }
+        if self.max_timeouts == 0 { return Err("max_timeouts can't be lower than 1") }
+        Ok(())
+    }
+
+    pub fn can_deal(&self, players: usize) -> bool { //Чи вистачить колоди, щоб роздати карти наданій кількості гравців
+        self.deck.size() >= self.hand_size * players
+    }
+}
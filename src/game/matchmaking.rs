@@ -0,0 +1,249 @@
+use std::{sync::Arc, time::{ Duration, Instant }};
+use sea_orm::{prelude::Uuid, DatabaseConnection};
+use serde::{ Serialize, Deserialize };
+use tokio::sync::{ RwLock, RwLockWriteGuard, broadcast::Sender };
+use crate::{
+    game::{ gameplay::rules::RuleSet, rooms::Room },
+    gateway::payloads::{ Event, Payload },
+    runtime_storage::Table,
+};
+
+const MIN_PLAYERS: usize = 2; //Межі бажаної кількості гравців
+const MAX_PLAYERS: usize = 8;
+const BASE_TOLERANCE: f64 = 100.0; //Допустима різниця рейтингів одразу після входу в чергу
+const TOLERANCE_PER_SECOND: f64 = 10.0; //На скільки допустима різниця зростає за кожну секунду очікування
+const TICK: Duration = Duration::from_secs(2); //Інтервал підбору гравців
+
+#[derive(Debug)]
+pub enum Error { //Список помилок черги
+    BadPlayerCount,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ticket { //Структура, що описує гравця в черзі
+    id: Uuid, //Ідентифікатор гравця
+    sender: Sender<Event>, //Надсилач
+    rating: f64, //Рейтинг на момент входу в чергу
+    players: usize, //Бажана кількість гравців
+    joined: Instant, //Час входу в чергу
+}
+
+impl Ticket {
+    fn tolerance(&self, now: Instant) -> f64 { //Допустима різниця рейтингів з урахуванням часу очікування
+        BASE_TOLERANCE + now.duration_since(self.joined).as_secs_f64() * TOLERANCE_PER_SECOND
+    }
+
+    fn status(&self, queued: bool) -> QueueStatus { //Формування статусу для гравця
+        QueueStatus { queued, players: self.players, waited: self.joined.elapsed().as_secs() }
+    }
+
+    fn notify_left(&self) { //Повідомлення гравця, що він більше не в черзі
+        let _ = self.sender.send(Payload::QueueStatus(self.status(false)).to_event());
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueueStatus { //Структура, що описує подію статусу черги
+    queued: bool, //чи перебуває гравець у черзі
+    players: usize, //бажана кількість гравців
+    waited: u64, //скільки секунд гравець вже очікує
+}
+
+#[derive(Debug, Default)]
+pub struct Queue(Vec<Ticket>); //Черга пошуку гри
+
+impl Queue {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn join(&mut self, id: Uuid, sender: Sender<Event>, rating: f64, players: usize) -> Result<QueueStatus, Error> { //Метод входу в чергу, повторний вхід замінює попередній
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) { return Err(Error::BadPlayerCount) }
+        self.0.retain(|ticket| ticket.id != id);
+        let ticket = Ticket { id, sender, rating, players, joined: Instant::now() };
+        let status = ticket.status(true);
+        self.0.push(ticket);
+        Ok(status)
+    }
+
+    pub fn leave(&mut self, id: &Uuid) -> Option<QueueStatus> { //Метод виходу з черги, None якщо гравця в черзі не було
+        let index = self.0.iter().position(|ticket| ticket.id == *id)?;
+        Some(self.0.remove(index).status(false))
+    }
+
+    pub fn cancel(&mut self, id: &Uuid) { //Вихід з черги при вході в кімнату вручну, гравець отримує статус поза чергою
+        let Some(index) = self.0.iter().position(|ticket| ticket.id == *id) else { return };
+        self.0.remove(index).notify_left();
+    }
+
+    fn requeue(&mut self, tickets: Vec<Ticket>) { //Повернення гравців у чергу з тим самим часом очікування
+        for ticket in tickets {
+            if !self.0.iter().any(|queued| queued.id == ticket.id) { self.0.push(ticket) }
+        }
+    }
+
+    pub fn update_sender(&mut self, id: &Uuid, sender: Sender<Event>) { //Відновлення сесії гравця в черзі
+        if let Some(ticket) = self.0.iter_mut().find(|ticket| ticket.id == *id) {
+            ticket.sender = sender;
+            let _ = ticket.sender.send(Payload::QueueStatus(ticket.status(true)).to_event());
+        }
+    }
+
+    fn take_matches(&mut self) -> Vec<Vec<Ticket>> { //Підбір груп гравців з однаковою бажаною кількістю та близьким рейтингом
+        let now = Instant::now();
+        let mut groups = Vec::new();
+        for size in MIN_PLAYERS..=MAX_PLAYERS {
+            let mut candidates: Vec<Ticket> = self.0.iter().filter(|ticket| ticket.players == size).cloned().collect();
+            candidates.sort_by(|a, b| a.rating.total_cmp(&b.rating)); //сусіди за рейтингом стоять поруч
+            let mut start = 0;
+            while start + size <= candidates.len() {
+                let window = &candidates[start..start + size];
+                let spread = window[size - 1].rating - window[0].rating;
+                //група підходить, якщо різниця рейтингів не перевищує допуск жодного з гравців
+                if window.iter().all(|ticket| spread <= ticket.tolerance(now)) {
+                    groups.push(window.to_vec());
+                    start += size;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        self.0.retain(|ticket| !groups.iter().flatten().any(|matched| matched.id == ticket.id)); //видалення підібраних гравців з черги
+        groups
+    }
+}
+
+async fn create_room(
+    group: Vec<Ticket>,
+    db: &Arc<DatabaseConnection>,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    queue_ptr: &Arc<RwLock<Queue>>,
+    lobby_ptr: &Arc<RwLock<crate::Lobby>>,
+) -> Option<Room> { //Перевірка підібраної групи та створення для неї кімнати
+    let mut players = players_ptr.write().await;
+    let mut rooms = rooms_ptr.write().await;
+    //поки група чекала, гравець міг відключитися або увійти в кімнату вручну
+    let (available, gone): (Vec<Ticket>, Vec<Ticket>) = group.into_iter()
+        .partition(|ticket| players.get(&ticket.id).is_some_and(|player| player.room.is_none() && player.spectating.is_none()));
+    if !gone.is_empty() { //такі гравці виходять з черги, а решта повертається в неї
+        for ticket in gone.iter() { ticket.notify_left() }
+        queue_ptr.write().await.requeue(available);
+        return None
+    }
+    let room = start_room(&available, db, lobby_ptr, &mut players, &mut rooms).await;
+    if room.is_none() { //кімнату не вдалося створити, група повідомляється про вихід з черги
+        for ticket in available.iter() { ticket.notify_left() }
+    }
+    room
+}
+
+async fn start_room(
+    group: &[Ticket],
+    db: &Arc<DatabaseConnection>,
+    lobby_ptr: &Arc<RwLock<crate::Lobby>>,
+    players: &mut RwLockWriteGuard<'_, crate::Players>, //замки вже взяті для перевірки групи
+    rooms: &mut RwLockWriteGuard<'_, crate::Rooms>,
+) -> Option<Room> { //Створення кімнати для групи та початок гри
+    let owner = group.first()?;
+    let mut room = Room::create(String::from("Matchmaking"), false, None, owner.id, group.len(), RuleSet::default(), owner.sender.clone()).await.ok()?;
+    room.set_lobby(lobby_ptr.read().await.feed()); //кімната приватна, але власник може зробити її публічною
+    for ticket in group.iter().skip(1) {
+        room.join(None, ticket.id, ticket.sender.clone()).await.ok()?;
+    }
+    for ticket in group.iter() { //усі підібрані гравці одразу готові
+        room.player_switch_ready(ticket.id).await.ok()?;
+    }
+    while rooms.get(&room.clone()).is_some() { //доки в таблиці вже існує кімната з таким ідентифікатором
+        room.regenerate_id()
+    };
+    rooms.insert(room.clone());
+    if room.start_game(None).await.is_err() { //гра не почалася, кімната видаляється
+        rooms.remove(&room);
+        return None
+    }
+    for ticket in group.iter() { //задання кімнати для відновлення сесії
+        if let Some(mut player) = players.get(&ticket.id).cloned() {
+            player.room = Some(room.id().clone());
+            players.replace(player);
+        }
+    }
+    rooms.replace(room.clone());
+    room.watch_turn(db.clone()); //запуск таймера першого ходу
+    Some(room)
+}
+
+pub async fn run( //Фоновий процес підбору гравців
+    queue_ptr: Arc<RwLock<Queue>>,
+    db: Arc<DatabaseConnection>,
+    players_ptr: Arc<RwLock<crate::Players>>,
+    rooms_ptr: Arc<RwLock<crate::Rooms>>,
+    lobby_ptr: Arc<RwLock<crate::Lobby>>,
+) {
+    loop {
+        tokio::time::sleep(TICK).await;
+        let groups = queue_ptr.write().await.take_matches();
+        for group in groups {
+            let senders: Vec<Sender<Event>> = group.iter().map(|ticket| ticket.sender.clone()).collect();
+            if let Some(room) = create_room(group, &db, &players_ptr, &rooms_ptr, &queue_ptr, &lobby_ptr).await {
+                let content = Payload::MatchFound(room.id().clone()).to_event();
+                for sender in senders { //повідомити гравців про знайдену гру
+                    let _ = sender.send(content.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast;
+
+    fn queue(tickets: &[(f64, usize)]) -> (Queue, Vec<Uuid>) { //Черга з гравців з наданими рейтингом та бажаною кількістю гравців
+        let (sender, _receiver) = broadcast::channel::<Event>(1);
+        let mut queue = Queue::new();
+        let ids = tickets.iter().map(|(rating, players)| {
+            let id = Uuid::new_v4();
+            queue.join(id, sender.clone(), *rating, *players).unwrap();
+            id
+        }).collect();
+        (queue, ids)
+    }
+
+    fn sorted(mut ids: Vec<Uuid>) -> Vec<Uuid> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn matches_players_with_same_size_and_close_rating() {
+        let (mut queue, ids) = queue(&[(1500.0, 2), (1550.0, 2), (1520.0, 3), (1900.0, 2)]);
+        let groups = queue.take_matches();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(sorted(groups[0].iter().map(|ticket| ticket.id).collect()), sorted(vec![ids[0], ids[1]]));
+        //підібрані гравці виходять з черги, решта залишається
+        assert_eq!(sorted(queue.0.iter().map(|ticket| ticket.id).collect()), sorted(vec![ids[2], ids[3]]));
+    }
+
+    #[test]
+    fn groups_have_requested_size() {
+        let (mut queue, _ids) = queue(&[(1500.0, 3), (1500.0, 3), (1500.0, 3), (1500.0, 3), (1500.0, 3)]);
+        let groups = queue.take_matches();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 3);
+        assert_eq!(queue.0.len(), 2);
+    }
+
+    #[test]
+    fn tolerance_grows_while_waiting() {
+        let (mut queue, _ids) = queue(&[(1500.0, 2), (1800.0, 2)]);
+        assert!(queue.take_matches().is_empty()); //різниця 300 більша за початковий допуск
+        let waited = Duration::from_secs_f64((300.0 - BASE_TOLERANCE) / TOLERANCE_PER_SECOND + 1.0);
+        for ticket in queue.0.iter_mut() {
+            ticket.joined -= waited;
+        }
+        assert_eq!(queue.take_matches().len(), 1);
+        assert!(queue.0.is_empty());
+    }
+}
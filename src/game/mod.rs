@@ -1,2 +1,4 @@
 pub mod rooms;
-pub mod gameplay;
\ No newline at end of file
+pub mod gameplay;
+pub mod matchmaking;
+pub mod rating;
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::f64::consts::{ LN_10, PI };
+
+pub const DEFAULT_RATING: f64 = 1500.0; //Рейтинг нового гравця
+pub const DEFAULT_DEVIATION: f64 = 350.0; //Відхилення рейтингу нового гравця, чим воно більше тим швидше змінюється рейтинг
+const MIN_DEVIATION: f64 = 30.0; //Мінімальне відхилення, щоб рейтинг досвідчених гравців не застигав
+
+const Q: f64 = LN_10 / 400.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Rating { //Структура, що описує рейтинг гравця за системою Glicko
+    pub rating: f64,
+    pub deviation: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self { rating: DEFAULT_RATING, deviation: DEFAULT_DEVIATION }
+    }
+}
+
+fn g(deviation: f64) -> f64 { //Вага суперника, тим менша чим менш визначений його рейтинг
+    1.0 / (1.0 + 3.0 * Q.powi(2) * deviation.powi(2) / PI.powi(2)).sqrt()
+}
+
+fn expected(own: &Rating, other: &Rating) -> f64 { //Очікуваний результат гри проти суперника
+    1.0 / (1.0 + 10_f64.powf(-g(other.deviation) * (own.rating - other.rating) / 400.0))
+}
+
+//ratings - рейтинги гравців у порядку вибування, гравець з меншим індексом вважається переможцем кожного з наступних
+pub fn update(ratings: &[Rating]) -> Vec<Rating> { //Обчислення нових рейтингів після гри з кількома гравцями
+    ratings.iter().enumerate().map(|(i, own)| {
+        let mut variance_inv = 0.0; //1/d²
+        let mut delta = 0.0; //Σ g(RDj)(s - E)
+        for (j, other) in ratings.iter().enumerate() {
+            if i == j { continue }
+            let e = expected(own, other);
+            let weight = g(other.deviation);
+            let score = if i < j { 1.0 } else { 0.0 };
+            variance_inv += Q.powi(2) * weight.powi(2) * e * (1.0 - e);
+            delta += weight * (score - e);
+        }
+        if variance_inv == 0.0 { return *own } //суперників немає, рейтинг не змінюється
+        let denominator = 1.0 / own.deviation.powi(2) + variance_inv;
+        Rating {
+            rating: own.rating + Q / denominator * delta,
+            deviation: (1.0 / denominator).sqrt().clamp(MIN_DEVIATION, DEFAULT_DEVIATION),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    #[test]
+    fn rating_is_conserved_for_equal_players() {
+        for players in 2..=6 {
+            let ratings = vec![Rating::default(); players];
+            let total: f64 = update(&ratings).iter().map(|rating| rating.rating).sum();
+            assert!((total - DEFAULT_RATING * players as f64).abs() < EPSILON, "{players} players: {total}");
+        }
+    }
+
+    #[test]
+    fn earlier_place_gains_more() {
+        let new = update(&[Rating::default(); 3]);
+        assert!(new[0].rating > DEFAULT_RATING);
+        assert!(new[0].rating > new[1].rating && new[1].rating > new[2].rating);
+        assert!((new[1].rating - DEFAULT_RATING).abs() < EPSILON); //середнє місце серед рівних не змінює рейтинг
+    }
+
+    #[test]
+    fn deviation_shrinks_within_bounds() {
+        for rating in update(&[Rating::default(); 4]) {
+            assert!(rating.deviation < DEFAULT_DEVIATION && rating.deviation >= MIN_DEVIATION);
+        }
+    }
+
+    #[test]
+    fn single_player_is_unchanged() {
+        let new = update(&[Rating { rating: 1700.0, deviation: 100.0 }]);
+        assert_eq!((new[0].rating, new[0].deviation), (1700.0, 100.0));
+    }
+}
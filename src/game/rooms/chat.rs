@@ -0,0 +1,64 @@
+use std::{collections::{ HashMap, VecDeque }, sync::Arc, time::{ Duration, Instant }};
+use serde::{ Serialize, Deserialize };
+use sea_orm::prelude::Uuid;
+use tokio::sync::RwLock;
+
+const HISTORY_SIZE: usize = 50; //Кількість останніх повідомлень, які отримує гравець при приєднанні
+pub const MAX_LENGTH: usize = 300; //Максимальна довжина повідомлення у символах
+const RATE_LIMIT: usize = 5; //Кількість повідомлень, дозволених одному гравцю за RATE_WINDOW
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+pub type Chat = Arc<RwLock<History>>; //Посилання на чат, спільне для усіх копій кімнати
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Emote { //Список наперед визначених реакцій
+    Hello,
+    GoodGame,
+    WellPlayed,
+    Oops,
+    Thinking,
+    Laugh,
+    Angry,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum Content { //Вміст повідомлення
+    Text(String),
+    Emote(Emote),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Message { //Структура, що описує повідомлення чату кімнати
+    author: Uuid, //Автор
+    content: Content, //Вміст
+    sent_at: i64, //Час надсилання у мілісекундах Unix
+}
+
+#[derive(Debug, Default)]
+pub struct History { //Структура, що описує історію чату та обмеження частоти повідомлень
+    messages: VecDeque<Message>, //останні повідомлення
+    sent: HashMap<Uuid, VecDeque<Instant>>, //час останніх повідомлень кожного гравця
+}
+
+impl History {
+    pub fn new() -> Chat { //Конструктор порожнього чату
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn messages(&self) -> &VecDeque<Message> { //Геттер історії
+        &self.messages
+    }
+
+    //Метод додавання повідомлення, повертає None якщо автор перевищив обмеження частоти
+    pub fn push(&mut self, author: Uuid, content: Content) -> Option<Message> {
+        let now = Instant::now();
+        let sent = self.sent.entry(author).or_default();
+        while sent.front().is_some_and(|time| now.duration_since(*time) > RATE_WINDOW) { sent.pop_front(); } //видалення застарілих позначок
+        if sent.len() >= RATE_LIMIT { return None }
+        sent.push_back(now);
+        let message = Message { author, content, sent_at: chrono::Utc::now().timestamp_millis() };
+        if self.messages.len() >= HISTORY_SIZE { self.messages.pop_front(); }
+        self.messages.push_back(message.clone());
+        Some(message)
+    }
+}
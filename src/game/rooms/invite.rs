@@ -0,0 +1,35 @@
+use serde::Serialize;
+use sea_orm::prelude::Uuid;
+
+pub const MAX_EXPIRY: u64 = 7 * 24 * 60 * 60; //Максимальний час дії запрошення у секундах
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Invite { //Структура, що описує код запрошення до кімнати
+    code: String, //Код
+    created_by: Uuid, //Хто створив запрошення
+    expires_at: i64, //Час закінчення дії у мілісекундах Unix
+    uses_left: Option<u32>, //Скільки разів ще можна використати, None - без обмеження
+}
+
+impl Invite {
+    pub fn new(created_by: Uuid, expires_in: u64, max_uses: Option<u32>) -> Self { //Конструктор
+        Self {
+            code: random_string::generate(10, "ABCDEFGHJKLMNPQRSTUVWXYZ23456789"), //без схожих символів, щоб код було легко передати
+            created_by,
+            expires_at: chrono::Utc::now().timestamp_millis() + expires_in as i64 * 1000,
+            uses_left: max_uses,
+        }
+    }
+
+    pub fn code(&self) -> &String { //Геттер коду
+        &self.code
+    }
+
+    pub fn is_valid(&self) -> bool { //Чи можна ще використати запрошення
+        self.expires_at > chrono::Utc::now().timestamp_millis() && self.uses_left != Some(0)
+    }
+
+    pub fn consume(&mut self) { //Використання запрошення
+        if let Some(uses) = self.uses_left.as_mut() { *uses = uses.saturating_sub(1) }
+    }
+}
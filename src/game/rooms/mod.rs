@@ -1,18 +1,30 @@
+pub mod chat;
+pub mod invite;
+pub mod password;
 pub mod player;
+pub mod reaper;
+pub mod series;
+pub mod spectator;
 
-use std::{borrow::Borrow, hash::Hash, ops::Deref, sync::Arc};
-use sea_orm::prelude::Uuid;
-use tokio::sync::{ RwLock, broadcast::Sender };
+use std::{borrow::Borrow, collections::{ HashMap, HashSet }, hash::Hash, ops::Deref, sync::{ atomic::{ AtomicI64, Ordering }, Arc }, time::Duration};
+use sea_orm::{prelude::Uuid, DatabaseConnection};
+use tokio::{ sync::{ Mutex, RwLock, broadcast::Sender }, task::JoinHandle };
 use random_string;
 use serde::{ser::SerializeStruct, Serialize};
 use player::Player;
+use spectator::{ Spectator, Spectators };
+use chat::{ Chat, Content, History };
+use invite::Invite;
+use password::Password;
+use series::{ Series, SharedSeries, Standing, Target };
 use crate::{
-    gateway::{ events::TableEvents, payloads::Payload },
-    runtime_storage::{ DataTable, SharedTable },
-    game::gameplay::Ok,
+    gateway::{ events::TableEvents, lobby::{ self, LobbyFeed }, payloads::{ Event, Payload } },
+    runtime_storage::{ DataTable, SharedTable, Table },
+    game::gameplay::{ Ok, player::Losers },
+    database::queries,
 };
 use futures::executor;
-use super::gameplay::{self, Game};
+use super::gameplay::{self, Game, bot::Bot, rules::RuleSet};
 
 #[derive(Debug, Serialize)]
 pub enum Error<'a> { //Список усіх помилок кімнат
@@ -23,6 +35,8 @@ pub enum Error<'a> { //Список усіх помилок кімнат
     NoGame,
     GameAlreadyStarted,
     Full,
+    SpectatingNotAllowed,
+    RateLimited,
     Game(gameplay::Error)
 }
 
@@ -31,11 +45,25 @@ pub struct Room { //Структура, що описує кімнату
     id: String, //Ідентифікатор
     name: String, //Назва
     pub is_public: bool, //Видимість в списку кімнат
-    password: Option<String>, //Пароль
+    password: Option<Password>, //Солений хеш пароля
     owner: Uuid, //Власник
     max_players: usize, //Максимальна кількість гравців
+    rules: RuleSet, //Правила гри
     players: Arc<RwLock<DataTable<Player>>>, //Посилання на таблицю гравців кімнати
+    pub allow_spectators: bool, //Чи дозволено спостерігати за кімнатою
+    spectator_delay: u64, //Затримка подій гри для глядачів у секундах
+    spectators: Spectators, //Посилання на таблицю глядачів кімнати
+    auto_start: u64, //Тривалість відліку до автоматичного початку гри у секундах, 0 - вимкнено
+    countdown: Arc<Mutex<Option<JoinHandle<()>>>>, //Посилання на запущений відлік, спільне для усіх копій кімнати
+    turn_timer: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>, //Посилання на таймер поточного ходу, спільне для усіх копій кімнати
+    chat: Chat, //Посилання на історію чату кімнати
+    series: SharedSeries, //Посилання на поточну серію ігор кімнати
+    banned: HashSet<Uuid>, //Гравці, яким заборонено приєднуватися до кімнати
+    invites: HashMap<String, Invite>, //Коди запрошень, що дозволяють приєднатися без пароля
+    created_at: i64, //Час створення у мілісекундах Unix
+    last_activity: Arc<AtomicI64>, //Час останньої дії гравців у мілісекундах Unix, спільний для усіх копій кімнати
     pub game: Option<Arc<RwLock<Game>>>, //посилання на об'єкт гри
+    lobby: Option<LobbyFeed>, //надсилач змін лобі, підписникам якого оголошуються зміни публічної кімнати
 }
 
 impl Default for Room
@@ -48,8 +76,22 @@ impl Default for Room
             password: None,
             owner: Uuid::default(),
             max_players: 2,
+            rules: RuleSet::default(),
             players: Arc::new(RwLock::new(DataTable::new())),
+            allow_spectators: false,
+            spectator_delay: 0,
+            spectators: Arc::new(RwLock::new(DataTable::new())),
+            auto_start: 0,
+            countdown: Arc::new(Mutex::new(None)),
+            turn_timer: Arc::new(std::sync::Mutex::new(None)),
+            chat: History::new(),
+            series: Series::new(),
+            banned: HashSet::new(),
+            invites: HashMap::new(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            last_activity: Arc::new(AtomicI64::new(chrono::Utc::now().timestamp_millis())),
             game: None,
+            lobby: None,
         }
     }
 }
@@ -57,12 +99,13 @@ impl Default for Room
 impl<'a, 'b> Room
 {
     //Метод створення кімнати
-    pub async fn create(name: String, is_public: bool, password: Option<String>, owner: Uuid, max_players: usize, sender: Sender<String>) -> Result<Self, Error<'b>> {
+    pub async fn create(name: String, is_public: bool, password: Option<String>, owner: Uuid, max_players: usize, rules: RuleSet, sender: Sender<Event>) -> Result<Self, Error<'b>> {
         let mut room = Self::default(); //Створення нової кімнати з значеннями за замовченням
         room.set_name(name)?; //задання назви,
         room.is_public = is_public; //публічності,
         room.set_password(password.clone())?; //пароля,
         room.set_max_players(max_players)?; //макс. кільк. гравців,
+        room.set_rules(rules)?; //правил гри,
         room.players.write().await.0.insert(Player::new(owner, sender));
         room.set_owner(owner.clone())?; //назначення його власником
         //при помилці будь-якого сеттера, створення завершиться помилкою, інакше метод повертає екземпляр
@@ -70,7 +113,8 @@ impl<'a, 'b> Room
     }
 
     //Оголошення повідомлення
-    pub fn announce(&self, content: String ) {
+    pub fn announce(&self, content: Event ) {
+        spectator::announce(&self.spectators, content.clone(), 0); //глядачі отримують події кімнати без затримки
         let room = self.clone();
         tokio::spawn(async move { //виділення потоку на кожне повідомлення
             for player in &**room.players().read().await {
@@ -95,8 +139,28 @@ impl<'a, 'b> Room
         &self.name
     }
 
-    pub fn password(&self) -> &Option<String> { //Геттер пароля
-        &self.password
+    pub fn created_at(&self) -> &i64 { //Геттер часу створення
+        &self.created_at
+    }
+
+    pub fn last_activity(&self) -> i64 { //Геттер часу останньої дії
+        self.last_activity.load(Ordering::Relaxed)
+    }
+
+    fn touch(&self) { //Оновлення часу останньої дії
+        self.last_activity.store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn has_password(&self) -> bool { //Чи має кімната пароль, сам пароль не розкривається
+        self.password.is_some()
+    }
+
+    fn check_password(&self, password: Option<&String>) -> Result<(), Error<'b>> { //Перевірка наданого пароля
+        match (&self.password, password) {
+            (None, _) => Ok(()), //кімната без пароля
+            (Some(hash), Some(password)) if hash.verify(password) => Ok(()),
+            _ => Err(Error::Forbidden("Wrong password")),
+        }
     }
 
     pub fn owner(&self) -> &Uuid { //Геттер власника
@@ -107,14 +171,42 @@ impl<'a, 'b> Room
         &self.max_players
     }
 
+    pub fn rules(&self) -> &RuleSet { //Геттер правил гри
+        &self.rules
+    }
+
     pub fn players(&self) -> &Arc<RwLock<DataTable<Player>>> { //Геттер посилання на таблицю гравців
         &self.players
     }
 
+    pub fn spectator_delay(&self) -> &u64 { //Геттер затримки для глядачів
+        &self.spectator_delay
+    }
+
+    pub fn auto_start(&self) -> &u64 { //Геттер тривалості відліку до автоматичного початку гри
+        &self.auto_start
+    }
+
+    pub fn series(&self) -> &SharedSeries { //Геттер посилання на серію ігор
+        &self.series
+    }
+
+    pub fn spectators(&self) -> &Spectators { //Геттер посилання на таблицю глядачів
+        &self.spectators
+    }
+
     pub fn game(&self) -> &Option<Arc<RwLock<Game>>> { //Геттер посилання на гру
         &self.game
     }
 
+    pub fn set_lobby(&mut self, lobby: LobbyFeed) { //Сеттер лобі, без нього кімната не з'являється у списку підписників
+        self.lobby = Some(lobby)
+    }
+
+    fn lobby_update(&self) { //Оголошення стану кімнати підписникам лобі, якщо кімната публічна
+        if let Some(feed) = &self.lobby { lobby::update(feed, self) }
+    }
+
     pub fn set_name(&mut self, name: String) -> Result<(), Error<'b>> { //Сеттер назви
         if !name.is_empty() { self.name = name; } //якщо рядок name не пустий то назначити його у якості нової назви
         else { return Err(Error::BadArgument("name can't be an empty string")) } //інакше повернути помилку
@@ -124,7 +216,7 @@ impl<'a, 'b> Room
     pub fn set_password(&mut self, password: Option<String>) -> Result<(), Error<'b>> { //Сеттер пароля
         if let Some(ref pass) = password { //якщо пароль не None(Null мови програмування Rust)
             if pass.len() <= 32 {  //якщо довжина <= 32
-                self.password = if pass.is_empty() { None } else { password } //Якщо рядок пустий то пароля немає інакше назначити рядок новим паролем
+                self.password = if pass.is_empty() { None } else { Some(Password::new(pass)) } //Якщо рядок пустий то пароля немає інакше зберегти хеш нового пароля
             }
             else { return Err(Error::BadArgument("password can't be longer than 32 characters")) } //якщо пароль більший то видати помилку
         }
@@ -138,63 +230,284 @@ impl<'a, 'b> Room
 
     pub fn set_max_players(&mut self, max_players: usize) -> Result<(), Error<'b>> { //Сеттер макс. кільк. гравців
         if max_players < 2 { return Err( Error::BadArgument("max_players can't be lower than 2") ) }
+        else if max_players < self.rules.min_players { return Err( Error::BadArgument("max_players can't be lower than min_players of the rules") ) }
+        else if !self.rules.can_deal(max_players) { return Err( Error::BadArgument("deck is too small to deal hands to max_players") ) }
         else { self.max_players = max_players }
         Ok(())
     }
 
+    pub fn set_spectator_delay(&mut self, delay: u64) -> Result<(), Error<'b>> { //Сеттер затримки для глядачів
+        if delay > 300 { return Err( Error::BadArgument("spectator_delay can't be longer than 300 seconds") ) }
+        self.spectator_delay = delay;
+        Ok(())
+    }
+
+    pub fn set_auto_start(&mut self, seconds: u64) -> Result<(), Error<'b>> { //Сеттер тривалості відліку до автоматичного початку гри
+        if seconds > 60 { return Err( Error::BadArgument("auto_start can't be longer than 60 seconds") ) }
+        self.auto_start = seconds;
+        Ok(())
+    }
+
+    //Метод початку нової серії ігор з наданою умовою завершення, очки гравців з попередніх ігор обнуляються
+    pub async fn set_series(&'a self, target: Target) -> Result<(), Error<'b>> {
+        target.validate().map_err(Error::BadArgument)?;
+        if let Some(game) = &self.game {
+            if !*game.read().await.gameover() { return Err(Error::GameAlreadyStarted) } //умову не можна змінити під час гри
+        }
+        self.series.write().await.reset(target);
+        self.reset_points().await;
+        Ok(())
+    }
+
+    async fn reset_points(&'a self) { //Метод обнулення очків гравців кімнати
+        let mut players = self.players.write().await;
+        let ids: Vec<Uuid> = players.iter().map(|player| player.id).collect();
+        for id in ids {
+            let _ = players.shared_update(&id, |player| {
+                player.points = 0;
+                Ok::<(), ()>(())
+            });
+        }
+    }
+
+    pub fn set_rules(&mut self, rules: RuleSet) -> Result<(), Error<'b>> { //Сеттер правил гри
+        rules.validate().map_err(Error::BadArgument)?; //повернути помилку якщо правила некоректні
+        if rules.min_players > self.max_players { return Err( Error::BadArgument("min_players can't be greater than max_players") ) }
+        if !rules.can_deal(self.max_players) { return Err( Error::BadArgument("deck is too small to deal hands to max_players") ) } //гра роздає карти усім готовим гравцям
+        self.rules = rules;
+        Ok(())
+    }
+
     //Метод приєднання гравця до кімнати
-    pub async fn join(&'a self, password: Option<String>, player_id: Uuid, sender: Sender<String>) -> Result<(), Error<'b>> {
+    pub async fn join(&'a self, password: Option<String>, player_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> {
+        self.check_password(password.as_ref())?; //якщо кімната має пароль і він не збігається з вказаним, то видати помилку
+        self.add_player(player_id, sender).await
+    }
+
+    //Метод приєднання гравця за кодом запрошення, пароль не потрібен
+    pub async fn join_by_invite(&'a mut self, code: &str, player_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> {
+        if !self.invites.get(code).is_some_and(|invite| invite.is_valid()) { return Err(Error::Forbidden("Invite is expired or revoked")) }
+        self.add_player(player_id, sender).await?;
+        if let Some(invite) = self.invites.get_mut(code) { invite.consume() } //запрошення використовується тільки після успішного приєднання
+        Ok(())
+    }
+
+    async fn add_player(&'a self, player_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> { //Метод додавання гравця після перевірки доступу
+        self.touch();
+        if self.banned.contains(&player_id) { return Err(Error::Forbidden("Banned from this room")) } //якщо гравця заблоковано в кімнаті
         let mut players = self.players.write().await; //Получення замка на зміну об'єкта за посиланням
-        if let Some(pass) = &self.password { //якщо кімната має пароль
-            if Some(pass) != password.as_ref() { //і якщо він не дорівнює вказаному в аргументі
-                return Err(Error::Forbidden("Wrong password")); //то видати помилку
-            }
-        }
         if players.len() >= self.max_players && !players.contains(&player_id) { //якщо кількість гравців в кімнаті вже >= макс. кільк. гравців
             return Err(Error::Full) //то видати помилку
         }
-        
 
         //if self.players.contains(&player) { return Err(Error::PlayerAlreadyInRoom) }
-        players.shared_insert(player::Player::new(player_id, sender)); //Додати гравця до таблиці гравців кімнати
+        let player = player::Player::new(player_id, sender);
+        players.shared_insert(player.clone()); //Додати гравця до таблиці гравців кімнати
+        drop(players);
+        spectator::announce(&self.spectators, Payload::RoomPlayerNew(player).to_event(), 0); //глядачі також бачать нового гравця
+        self.lobby_update(); //кількість гравців змінилася
+        Ok(())
+    }
+
+    pub fn create_invite(&mut self, created_by: Uuid, expires_in: u64, max_uses: Option<u32>) -> Result<Invite, Error<'b>> { //Метод створення запрошення
+        if expires_in == 0 || expires_in > invite::MAX_EXPIRY { return Err(Error::BadArgument("expires_in must be between 1 second and 7 days")) }
+        if max_uses == Some(0) { return Err(Error::BadArgument("max_uses can't be 0")) }
+        let mut invite = Invite::new(created_by, expires_in, max_uses);
+        while self.invites.contains_key(invite.code()) { invite = Invite::new(created_by, expires_in, max_uses) } //код повинен бути унікальним
+        self.invites.insert(invite.code().clone(), invite.clone());
+        Ok(invite)
+    }
+
+    pub fn invites(&mut self) -> Vec<Invite> { //Метод отримання дійсних запрошень, недійсні видаляються
+        self.invites.retain(|_, invite| invite.is_valid());
+        self.invites.values().cloned().collect()
+    }
+
+    pub fn has_invite(&self, code: &str) -> bool { //Чи належить код запрошення цій кімнаті
+        self.invites.contains_key(code)
+    }
+
+    pub fn revoke_invite(&mut self, code: &str) -> Result<(), Error<'b>> { //Метод відкликання запрошення
+        self.invites.remove(code).map(|_| ()).ok_or(Error::BadArgument("invite not found"))
+    }
+
+    //Метод приєднання глядача до кімнати
+    pub async fn spectate(&'a self, password: Option<String>, spectator_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> {
+        self.check_password(password.as_ref())?; //пароль перевіряється так само як при приєднанні
+        self.add_spectator(spectator_id, sender).await
+    }
+
+    async fn add_spectator(&'a self, spectator_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> { //Метод додавання глядача після перевірки пароля
+        if !self.allow_spectators { return Err(Error::SpectatingNotAllowed) } //якщо власник не дозволив спостерігати
+        if self.banned.contains(&spectator_id) { return Err(Error::Forbidden("Banned from this room")) }
+        if self.players.read().await.contains(&spectator_id) { return Err(Error::Forbidden("Players can't spectate their own room")) }
+        self.spectators.write().await.0.replace(Spectator::new(spectator_id, sender.clone())); //Додати глядача до таблиці
+        let _ = sender.send(Payload::RoomCreate(self.clone()).to_event()); //надіслати глядачу інформацію про кімнату
+        if let Some(game) = &self.game { //та про гру, знімок надсилається з тією ж затримкою, що й події гри
+            let snapshot = Payload::GameStarted(Box::new(game.read().await.clone())).to_event();
+            spectator::send(sender, snapshot, self.spectator_delay);
+        }
         Ok(())
     }
 
+    //Метод виходу глядача з кімнати
+    pub async fn stop_spectating(&'a self, spectator_id: Uuid) -> Result<(), Error<'b>> {
+        if self.spectators.write().await.0.remove(&spectator_id) { Ok(()) } else { Err(Error::PlayerNotInRoom) }
+    }
+
+    //Метод відновлення сесії глядача
+    pub async fn spectator_update_sender(&'a self, spectator_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> {
+        if !self.spectators.read().await.contains(&spectator_id) { return Err(Error::PlayerNotInRoom) }
+        self.add_spectator(spectator_id, sender).await //пароль вже перевірено при першому приєднанні
+    }
+
+    //Метод надсилання повідомлення чату, повідомлення оголошується усім гравцям та глядачам кімнати
+    pub async fn send_message(&'a self, author: Uuid, content: Content) -> Result<(), Error<'b>> {
+        self.touch();
+        if !self.players.read().await.contains(&author) { return Err(Error::PlayerNotInRoom) } //писати можуть тільки гравці кімнати
+        let content = match content {
+            Content::Text(text) => {
+                let text = text.trim().to_string();
+                if text.is_empty() { return Err(Error::BadArgument("message can't be empty")) }
+                if text.chars().count() > chat::MAX_LENGTH { return Err(Error::BadArgument("message can't be longer than 300 characters")) }
+                Content::Text(text)
+            },
+            emote => emote,
+        };
+        let message = self.chat.write().await.push(author, content).ok_or(Error::RateLimited)?;
+        self.announce(Payload::RoomMessageNew(message).to_event());
+        Ok(())
+    }
+
+    //Метод додавання бота до кімнати
+    pub async fn add_bot(&'a self, bot: Bot) -> Result<Uuid, Error<'b>> {
+        let mut players = self.players.write().await;
+        if players.len() >= self.max_players { //бот займає місце гравця
+            return Err(Error::Full)
+        }
+        let player = player::Player::new_bot(bot);
+        let id = player.id;
+        players.shared_insert(player.clone());
+        drop(players);
+        spectator::announce(&self.spectators, Payload::RoomPlayerNew(player).to_event(), 0);
+        self.lobby_update();
+        Ok(id)
+    }
+
+    //Метод видалення гравця з кімнати власником, ban - також заборонити повторне приєднання
+    pub async fn kick(&'a mut self, player_id: Uuid, ban: bool) -> Result<Ok, Error<'b>> {
+        self.touch();
+        if player_id == self.owner { return Err(Error::Forbidden("Owner can't kick themselves")) }
+        let mut players = self.players.write().await;
+        let removed = players.get(&player_id).cloned();
+        if let Some(player) = &removed {
+            players.shared_remove(&player_id); //оголошення виходу гравця іншим гравцям
+            let _ = player.sender.send(Payload::RoomPlayerLeft(player_id).to_event()); //та самому гравцю
+        } else if !ban { //заблокувати можна і гравця, що вже вийшов
+            return Err(Error::PlayerNotInRoom)
+        }
+        drop(players);
+        if removed.is_some() {
+            spectator::announce(&self.spectators, Payload::RoomPlayerLeft(player_id).to_event(), 0);
+            self.lobby_update()
+        }
+        if ban { self.banned.insert(player_id); }
+        if removed.is_none() { return Ok(Ok::Ok) }
+        let Some(game) = &self.game else { return Ok(Ok::Ok) };
+        let result = game.write().await.kick(player_id); //видалити гравця з гри, якщо вона йде
+        if let Ok::GameOver(ref players) = result { self.finish_game(players).await; }
+        Ok(result)
+    }
+
     //Метод виходу гравця з кімнати
     pub async fn leave(&'a mut self, player_id: Uuid) -> Result<bool, Error<'b>> {
+        self.touch();
         let mut players = self.players.write().await; //замок на зміну таблиці гравців
         if !players.shared_remove(&player_id) { //спробувати видалити вказаного гравця
             return Err(Error::PlayerNotInRoom); //якщо змін не відбулося значить гравця немає в кімнати, повернути помилку
         };
+        spectator::announce(&self.spectators, Payload::RoomPlayerLeft(player_id).to_event(), 0); //оголошення виходу глядачам
         let changed = self.owner == player_id; //порівняня власника і гравця, що вийшов
         if changed { //якщо власник вийшов
-            self.owner = players.iter().next().ok_or(Error::CantAssignNewOwner)?.id; //назначити наступного гравця в таблиці
+            self.owner = players.iter().find(|player| player.bot.is_none()).ok_or(Error::CantAssignNewOwner)?.id; //назначити наступного гравця в таблиці, що не є ботом
             //якщо гравців не залишилося, повернути помилку
+        } else { //інакше кімната не замінюється в таблиці, тому лобі оновлюється тут
+            drop(players);
+            self.lobby_update();
         }
         Ok(changed)
     }
 
     pub async fn player_switch_ready(&'a self, player_id: Uuid) -> Result<(), Error<'b>> { //Метод перемикання готовності гравця
+        self.touch();
         if let Some(game) = &self.game {  //якщо кімната має розпочату гру
             if *game.read().await.gameover() == false { return Err(Error::GameAlreadyStarted); } //видати помилку якщо вона не завершена
         }
         let mut players = self.players.write().await; //замок на зміну таблиці гравців
+        let mut updated = None;
         players.shared_update(&player_id, |player| { //виклик методу пошуку гравця за id,
             //при знайденому гравці передати його в це замикання
             player.is_ready = !player.is_ready; //перемкнути готовність
+            updated = Some(player.clone());
             Ok::<(), ()>(()) //повернути пусте значення
         }).unwrap_or(None).ok_or(Error::PlayerNotInRoom)?; //Повернути помилку якщо гравець не в кімнаті
+        drop(players);
+        let is_ready = updated.as_ref().is_some_and(|player| player.is_ready);
+        if let Some(player) = updated { spectator::announce(&self.spectators, Payload::RoomPlayerUpdate(player).to_event(), 0) }
+        if !is_ready { self.cancel_countdown().await } //гравець більше не готовий, відлік скасовується
         Ok(())
     }
 
-    pub async fn player_update_sender(&'a self, player_id: Uuid, sender: Sender<String>) -> Result<(), Error<'b>> { //Метод відновлення сесії
+    async fn all_ready(&'a self) -> bool { //Чи готові усі гравці кімнати, якщо їх хоча б двоє
+        let players = self.players.read().await;
+        players.len() >= 2 && players.iter().all(|player| player.is_ready)
+    }
+
+    //Метод запуску відліку до автоматичного початку гри, якщо він увімкнений та усі гравці готові
+    pub async fn start_countdown(&'a self, db: Arc<DatabaseConnection>, rooms_ptr: Arc<RwLock<crate::Rooms>>) {
+        if self.auto_start == 0 || !self.all_ready().await { return }
+        if let Some(game) = &self.game {
+            if !*game.read().await.gameover() { return } //гра вже йде
+        }
+        let mut countdown = self.countdown.lock().await;
+        if countdown.is_some() { return } //відлік вже запущений
+        self.announce(Payload::RoomCountdown(self.auto_start).to_event());
+        let seconds = self.auto_start;
+        let room = self.clone();
+        *countdown = Some(tokio::spawn(async move { //відлік працює в окремому потоці
+            tokio::time::sleep(Duration::from_secs(seconds)).await;
+            //після цього відлік вже не можна перервати, але стан кімнати перевіряється повторно
+            if room.countdown.lock().await.take().is_none() { return }
+            let mut rooms = rooms_ptr.write().await;
+            let Some(mut room) = rooms.get(room.id()).cloned() else { return }; //кімната вже закрита
+            if room.auto_start == 0 || !room.all_ready().await { //поки йшов відлік, хтось вийшов чи приєднався або власник вимкнув відлік
+                room.announce(Payload::RoomCountdownCancel.to_event());
+                return
+            }
+            if room.start_game(None).await.is_ok() {
+                rooms.replace(room.clone());
+                room.watch_turn(db); //запуск таймера першого ходу
+            } else { //гру не вдалося створити, наприклад готових гравців менше за min_players
+                room.announce(Payload::RoomCountdownCancel.to_event());
+            }
+        }));
+    }
+
+    async fn cancel_countdown(&'a self) { //Метод скасування відліку
+        if let Some(countdown) = self.countdown.lock().await.take() {
+            countdown.abort();
+            self.announce(Payload::RoomCountdownCancel.to_event());
+        }
+    }
+
+    pub async fn player_update_sender(&'a self, player_id: Uuid, sender: Sender<Event>) -> Result<(), Error<'b>> { //Метод відновлення сесії
         let mut players = self.players.write().await; //замок на зміну таблиці гравця
         players.shared_update(&player_id, |player| { //пошук гравця за id, передача в замикання
             player.sender = sender.clone(); //та назначення нового надсилача
             Ok::<(), ()>(())
         }).unwrap_or(None).ok_or(Error::PlayerNotInRoom)?; //повернути помилку якщо гравця немає в кімнаті
         drop(players); //відімкнути таблицю гравців, таким чином даючи можливість іншим потокам взаємодіяти з значенням за посиланням
-        let _ = sender.send(Payload::RoomCreate(self.clone()).to_json_string()); //надіслати гравцю, що відновлює сесію інформацію про кімнату
+        let _ = sender.send(Payload::RoomCreate(self.clone()).to_event()); //надіслати гравцю, що відновлює сесію інформацію про кімнату
         if let Some(game) = &self.game { //якщо кімната має розпочату гру
             let mut game = game.write().await; //замок
             game.player_update_sender(player_id, sender.clone()); //також відновити сесію гравця в грі
@@ -202,58 +515,130 @@ impl<'a, 'b> Room
         Ok(())
     }
 
-    async fn new_game(&'a mut self) -> Result<(), Error<'b>> { //метод створення нової гри
-        let game_obj = Game::new(self.players.read().await.deref().deref().clone())
+    async fn new_game(&'a mut self, seed: Option<u64>) -> Result<(), Error<'b>> { //метод створення нової гри
+        let mut game_obj = Game::new(self.players.read().await.deref().deref().clone(), self.rules.clone(), seed)
             .map_err(|e| Error::Game(e))?; //Створення нового екземпляру гри, в якості аргумента надається клонована таблиця гравців кімнати
+        game_obj.set_spectators(self.spectators.clone(), self.spectator_delay); //передача глядачів кімнати до гри
         self.game = Some(Arc::new(RwLock::new(game_obj.clone()))); //створення розумного вказівника та поміщення його в game
         let game = self.game.as_ref().unwrap().read().await; //замок на читання об'єкту гри
-        game.announce(Payload::GameStarted(game_obj).to_json_string()); //оголошення створення нової гри
-        game.announce_turn(false); //оголошення наявних карт гравцям
+        game.announce(Payload::GameStarted(Box::new(game_obj)).to_event()); //оголошення створення нової гри
+        game.announce_hands(); //оголошення наявних карт гравцям
         Ok(())
     }
-    pub async fn start_game(&'a mut self) -> Result<(), Error<'b>> { //метод створення нової гри тільки у випадку якщо вона ще не розпочалася
+    pub async fn start_game(&'a mut self, seed: Option<u64>) -> Result<(), Error<'b>> { //метод створення нової гри тільки у випадку якщо вона ще не розпочалася
+        self.touch();
+        //seed - зерно генератора карт, None - згенерувати випадкове
         match &self.game {
             Some(game) => { //якщо вже є розпочата гра 
                 if *game.read().await.gameover() == true { //якщо вона закінчена
-                    let _ = self.new_game().await; //створити нову
-                    Ok(())
+                    self.new_game(seed).await //створити нову, передавши помилку створення
                 } else {
                     Err(Error::GameAlreadyStarted) //інакше повернути помилку
                 }
             },
             None => { //якщо гра не розпочалася
-                let _ = self.new_game().await; //створити нову
-                Ok(())
+                self.new_game(seed).await //створити нову
             }
         }
     }
 
     //Метод взаємодії з грою
-    pub async fn play_game(&'a self, player_id: Uuid, card_id: Option<usize>) -> Result<Ok, Error<'b>> {
+    pub async fn play_game(&'a self, player_id: Uuid, card_id: Option<u32>) -> Result<Ok, Error<'b>> {
         match &self.game {
             Some(game) => { //якщо гра розпочалася
                 let mut game =game.write().await; //замок на об'єкт гри
                 let result = game.play(player_id, card_id).map_err(|e| Error::Game(e))?; //зіграти та зберегти результат
-                match result {
-                    Ok::GameOver(ref players ) => { //якщо гра завершилася
-                        let mut room_players = self.players.write().await; //замок на гравців у кімнаті
-                        for loser in players.iter() { //ітерація усих гравців гри
-                            let _ = room_players.shared_update(loser.id(), |player| { //пошук гравців кімнати та оновлення їх значень
-                                player.is_ready = false; //усі гравці повинні бути не готові після гри
-                                player.points += loser.points(); //додавання очків з гри до очків з усих ігор
-                                Ok::<(), ()>(())
-                            });
-                        };
-                        self.announce(Payload::GameOver(players.clone()).to_json_string()); //оголошення кінця гри
-                    },
-                    _ => {},
-                };
+                self.touch(); //тільки успішний хід вважається активністю кімнати
+                if let Ok::GameOver(ref players) = result { //якщо гра завершилася
+                    self.finish_game(players).await;
+                }
                 Ok(result)
             },
             None => Err(Error::NoGame), //Повернення помилки у випадку якщо гра не розпочалася
         }
     }
 
+    async fn finish_game(&'a self, players: &Losers) { //метод оновлення гравців кімнати після завершення гри
+        let mut room_players = self.players.write().await; //замок на гравців у кімнаті
+        for loser in players.iter() { //ітерація усих гравців гри
+            let _ = room_players.shared_update(loser.id(), |player| { //пошук гравців кімнати та оновлення їх значень
+                player.is_ready = false; //усі гравці повинні бути не готові після гри
+                player.points += loser.points(); //додавання очків з гри до очків з усих ігор
+                Ok::<(), ()>(())
+            });
+        };
+        let content = Payload::GameOver(players.clone()).to_event();
+        for player in room_players.iter() { //оголошення кінця гри гравцям
+            let _ = player.sender.send(content.clone());
+        }
+        spectator::announce(&self.spectators, content, self.spectator_delay); //та глядачам із затримкою гри
+    }
+
+    pub async fn save_results(&'a self, db: &DatabaseConnection, players: &Losers) { //Метод збереження результатів гри у статистику акаунтів та історію ігор
+        queries::accounts::save_game(db, players).await;
+        if let Some(game) = &self.game {
+            let game = game.read().await.clone();
+            let _ = queries::matches::create(db, self.id.clone(), &game, players).await;
+        }
+        self.record_series(db, players).await;
+    }
+
+    async fn record_series(&'a self, db: &DatabaseConnection, players: &Losers) { //Метод запису гри до серії та завершення серії при виконанні умови
+        let standings: Vec<Standing> = self.players.read().await.iter()
+            .map(|player| Standing { id: player.id, points: player.points }) //очки гравців кімнати вже містять очки цієї гри
+            .collect();
+        let Some(result) = self.series.write().await.record(players.clone(), standings) else { return };
+        let content = Payload::SeriesOver(result.clone()).to_event();
+        for player in self.players.read().await.iter() { //оголошення підсумку серії гравцям
+            let _ = player.sender.send(content.clone());
+        }
+        spectator::announce(&self.spectators, content, self.spectator_delay); //та глядачам із затримкою гри
+        queries::accounts::save_series(db, &result).await;
+        self.reset_points().await; //наступна серія починається з нуля
+    }
+
+    pub fn watch_turn(&self, db: Arc<DatabaseConnection>) { //Метод запуску таймера поточного ходу
+        let room = self.clone();
+        let timer = tokio::spawn(async move { //таймер працює в окремому потоці
+            let Some(game) = room.game.clone() else { return };
+            let (turn_id, timeout, is_bot) = {
+                let game = game.read().await;
+                if *game.gameover() { return } //гра завершена або кімната закрита
+                (*game.turn_id(), game.turn_timeout(), game.is_bot_turn())
+            };
+            let result = if is_bot { //якщо ходить бот, то зіграти за нього після невеликої паузи
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                game.write().await.play_bot(turn_id)
+            } else {
+                let Some(timeout) = timeout else { return }; //якщо час на хід не обмежений, то таймер не потрібен
+                tokio::time::sleep(Duration::from_secs(timeout)).await; //зачекати закінчення ходу
+                game.write().await.timeout(turn_id) //якщо хід не змінився, то взяти карту за гравця та передати хід
+            };
+            match result {
+                Ok(Ok::GameOver(players)) => { //якщо після видалення гравця гра завершилася
+                    room.finish_game(&players).await;
+                    room.save_results(db.as_ref(), &players).await; //зберегти результати гри
+                },
+                Ok(Ok::Ok) => room.watch_turn(db), //запустити таймер наступного ходу
+                Err(_) => {}, //хід вже зроблений, таймер більше не потрібен
+            }
+        });
+        if let Ok(mut turn_timer) = self.turn_timer.lock() {
+            //таймер попереднього ходу вже не потрібен, навіть якщо цей метод викликаний з нього самого
+            if let Some(previous) = turn_timer.replace(timer) { previous.abort() }
+        }
+    }
+
+    pub async fn close(&'a self) { //Метод зупинки гри, таймерів та відліку перед видаленням кімнати
+        self.cancel_countdown().await;
+        if let Ok(mut turn_timer) = self.turn_timer.lock() {
+            if let Some(timer) = turn_timer.take() { timer.abort() }
+        }
+        if let Some(game) = &self.game {
+            game.write().await.abandon(); //таймери, що вже взяли хід, отримають помилку і не збережуть результати
+        }
+    }
+
 }
 
 
@@ -262,34 +647,43 @@ impl Serialize for Room {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: serde::Serializer {
-        let mut state = serializer.serialize_struct("room", 8)?;
+        let mut state = serializer.serialize_struct("room", 15)?;
         state.serialize_field("id", self.id())?;
         state.serialize_field("name", self.name())?;
         state.serialize_field("is_public", &self.is_public)?;
-        state.serialize_field("password", self.password() )?;
+        state.serialize_field("has_password", &self.has_password())?; //пароль не розкривається навіть гравцям кімнати
         state.serialize_field("owner", self.owner())?;
         state.serialize_field("max_players", self.max_players())?;
-        state.serialize_field("players", &*executor::block_on(self.players.read()))?; //показники не серіалізуються, саме тому власна реалізація необхідна
+        state.serialize_field("rules", self.rules())?;
+        state.serialize_field("allow_spectators", &self.allow_spectators)?;
+        state.serialize_field("spectator_delay", self.spectator_delay())?;
+        state.serialize_field("auto_start", self.auto_start())?;
+        state.serialize_field("series", &*executor::block_on(self.series.read()))?; //умова та результати поточної серії
+        state.serialize_field("players", &*executor::block_on(self.players.read()))?;
+        state.serialize_field("spectators", &*executor::block_on(self.spectators.read()))?; //показники не серіалізуються, саме тому власна реалізація необхідна
+        state.serialize_field("chat", executor::block_on(self.chat.read()).messages())?; //історія чату для гравців, що приєднуються
         state.serialize_field("game", &self.game().is_some())?;
         state.end()
     }
 }
 
 
+#[derive(Debug)]
 pub struct Partial(pub Room); //обгортка кімнати в нову структуру, серіалізація якої надає скорочену інформацію про кімнату
 impl Serialize for Partial {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: serde::Serializer {
-        let mut state = serializer.serialize_struct("room", 8)?;
+        let mut state = serializer.serialize_struct("room", 9)?;
         state.serialize_field("id", self.0.id())?;
         state.serialize_field("name", self.0.name())?;
         state.serialize_field("is_public", &self.0.is_public)?;
-        state.serialize_field("password", &self.0.password().is_some() )?;
+        state.serialize_field("has_password", &self.0.has_password())?;
         state.serialize_field("owner", self.0.owner())?;
         state.serialize_field("max_players", self.0.max_players())?;
         state.serialize_field("players", &executor::block_on(self.0.players.read()).len())?; //а саме замість гравців серіалізується тільки їх кількість
         state.serialize_field("game", &self.0.game().is_some())?;
+        state.serialize_field("created_at", self.0.created_at())?;
         state.end()
     }
 }
@@ -297,18 +691,21 @@ impl Serialize for Partial {
 //Реалізація Gateway подій для Room 
 impl TableEvents for Room {
     fn insert(&self) {
-        let content = Payload::RoomCreate(self.clone()).to_json_string(); //Сформувати новий Payload
-        self.announce(content) //Оголосити його
+        let content = Payload::RoomCreate(self.clone()).to_event(); //Сформувати новий Payload
+        self.announce(content); //Оголосити його
+        self.lobby_update() //та підписникам лобі, якщо кімната публічна
     }
 
     fn update(&self) {
-        let content = Payload::RoomUpdate(self.clone()).to_json_string();
-        self.announce(content)
+        let content = Payload::RoomUpdate(self.clone()).to_event();
+        self.announce(content);
+        self.lobby_update()
     }
 
     fn delete(&self) {
-        let content = Payload::RoomDelete(self.id().clone()).to_json_string();
-        self.announce(content)
+        let content = Payload::RoomDelete(self.id().clone()).to_event();
+        self.announce(content);
+        if let Some(feed) = &self.lobby { lobby::delete(feed, self) }
     }
 }
 
@@ -0,0 +1,33 @@
+use std::fmt;
+
+const SALT_CHARSET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+#[derive(Clone)]
+pub struct Password { //Структура, що описує солений хеш пароля кімнати
+    salt: String, //Сіль, унікальна для кожного пароля
+    hash: String, //sha256 від солі та пароля
+}
+
+impl Password {
+    pub fn new(password: &str) -> Self { //Конструктор, хешує пароль з новою сіллю
+        let salt = random_string::generate(16, SALT_CHARSET);
+        let hash = Self::digest(&salt, password);
+        Self { salt, hash }
+    }
+
+    fn digest(salt: &str, password: &str) -> String {
+        sha256::digest(format!("{}{}", salt, password))
+    }
+
+    pub fn verify(&self, password: &str) -> bool { //Перевірка пароля за сталий час, щоб час відповіді не підказував збіг префікса
+        let hash = Self::digest(&self.salt, password);
+        hash.len() == self.hash.len()
+            && hash.bytes().zip(self.hash.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+impl fmt::Debug for Password { //Хеш не виводиться навіть у журнал
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(..)")
+    }
+}
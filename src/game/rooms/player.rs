@@ -1,38 +1,44 @@
 use std::{borrow::Borrow, hash::Hash};
 use serde::Serialize;
 use sea_orm::prelude::Uuid;
-use tokio::sync::broadcast::Sender;
-use crate::{ gateway::events::SharedTableEvents, gateway::payloads::Payload };
+use tokio::sync::broadcast::{ self, Sender };
+use crate::{ gateway::events::SharedTableEvents, gateway::payloads::{ Event, Payload }, game::gameplay::bot::Bot };
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Player { //Структура, що описує гравця у кімнаті
     pub id: Uuid, //Ідентифікатор
     #[serde(skip)]
-    pub sender: Sender<String>, //Надсилач
+    pub sender: Sender<Event>, //Надсилач
     pub is_ready: bool, //Готовність
     pub points: u64, //Очки
+    pub bot: Option<Bot>, //Стратегія, якщо гравець є ботом
 }
 
 impl Player {
-    pub fn new(id: Uuid, sender: Sender<String>) -> Self { //Конструктор
-        Self { id, sender, is_ready: false, points: 0 }
+    pub fn new(id: Uuid, sender: Sender<Event>) -> Self { //Конструктор
+        Self { id, sender, is_ready: false, points: 0, bot: None }
+    }
+
+    pub fn new_bot(bot: Bot) -> Self { //Конструктор бота, бот завжди готовий
+        let (sender, _receiver) = broadcast::channel::<Event>(1); //бот не має з'єднання, тому повідомлення йому нікуди не надходять
+        Self { id: Uuid::new_v4(), sender, is_ready: true, points: 0, bot: Some(bot) }
     }
 }
 
 impl SharedTableEvents for Player { //Реалізація ознки SharedTableEvents для гравця
     //ця ознака надсилає зміни у будь-якому об'єкту таблиці усім її об'єктам
     fn insert(&self, other: Self) {
-        let content = Payload::RoomPlayerNew(other).to_json_string(); //формування Payload
+        let content = Payload::RoomPlayerNew(other).to_event(); //формування Payload
         let _ = self.sender.send(content); //Надсилання
     }
 
     fn update(&self, other: Self) {
-        let content = Payload::RoomPlayerUpdate(other).to_json_string();
+        let content = Payload::RoomPlayerUpdate(other).to_event();
         let _ = self.sender.send(content);
     }
 
     fn delete(&self, other: Self) {
-        let content = Payload::RoomPlayerLeft(other.id).to_json_string();
+        let content = Payload::RoomPlayerLeft(other.id).to_event();
         let _ = self.sender.send(content);
     }
 }
@@ -0,0 +1,39 @@
+use std::{sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+use crate::runtime_storage::Table;
+
+const TICK: Duration = Duration::from_secs(60); //Інтервал перевірки кімнат
+
+pub async fn run( //Фоновий процес закриття кімнат без активності
+    players_ptr: Arc<RwLock<crate::Players>>,
+    rooms_ptr: Arc<RwLock<crate::Rooms>>,
+    idle: Duration, //час без дій гравців, після якого кімната закривається
+) {
+    loop {
+        tokio::time::sleep(TICK).await;
+        let deadline = chrono::Utc::now().timestamp_millis() - idle.as_millis() as i64;
+        let mut players = players_ptr.write().await; //порядок блокування такий самий, як і в обробниках запитів
+        let mut rooms = rooms_ptr.write().await;
+        let idle_rooms: Vec<_> = rooms.iter().filter(|room| room.last_activity() < deadline).cloned().collect();
+        if idle_rooms.is_empty() {
+            continue;
+        }
+        for room in &idle_rooms {
+            room.close().await; //зупинка гри та таймерів, щоб вони не зберегли результати вже закритої кімнати
+            rooms.remove(room); //видалення кімнати оголошує RoomDelete гравцям, глядачам та лобі
+        }
+        let affected: Vec<_> = players.iter()
+            .filter(|player| idle_rooms.iter().any(|room| player.room.as_ref() == Some(room.id()) || player.spectating.as_ref() == Some(room.id())))
+            .cloned()
+            .collect();
+        for mut player in affected { //сесії більше не посилаються на закриту кімнату
+            if idle_rooms.iter().any(|room| player.room.as_ref() == Some(room.id())) {
+                player.room = None;
+            }
+            if idle_rooms.iter().any(|room| player.spectating.as_ref() == Some(room.id())) {
+                player.spectating = None;
+            }
+            players.replace(player);
+        }
+    }
+}
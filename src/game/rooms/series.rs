@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use serde::{ Serialize, Deserialize };
+use sea_orm::prelude::Uuid;
+use tokio::sync::RwLock;
+use crate::game::gameplay::player::Losers;
+
+const MAX_GAMES: u32 = 99; //Максимальна кількість ігор у серії
+
+pub type SharedSeries = Arc<RwLock<Series>>; //Посилання на серію, спільне для усіх копій кімнати
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Target { //Список умов завершення серії
+    #[default]
+    Single, //серія не ведеться, кожна гра окрема
+    Games(u32), //серія закінчується після вказаної кількості ігор
+    Points(u64), //серія закінчується, коли хоча б один гравець набирає вказану кількість очків
+}
+
+impl Target {
+    pub fn validate(&self) -> Result<(), &'static str> { //Перевірка умови на коректність
+        match self {
+            Target::Games(games) if *games == 0 || *games > MAX_GAMES => Err("series games must be between 1 and 99"),
+            Target::Points(0) => Err("series points must be greater than 0"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing { //Загальні очки гравця у серії
+    pub id: Uuid,
+    pub points: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesOver { //Підсумок завершеної серії
+    pub winner: Uuid, //переможець серії
+    pub standings: Vec<Standing>, //загальні очки гравців, від найбільших до найменших
+    pub games: Vec<Losers>, //результати кожної гри серії
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Series { //Структура, що описує поточну серію ігор кімнати
+    target: Target, //умова завершення
+    games: Vec<Losers>, //результати зіграних ігор серії
+}
+
+impl Series {
+    pub fn new() -> SharedSeries { //Конструктор без серії
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    pub fn target(&self) -> &Target { //Геттер умови завершення
+        &self.target
+    }
+
+    pub fn reset(&mut self, target: Target) { //Метод початку нової серії з наданою умовою
+        self.target = target;
+        self.games.clear();
+    }
+
+    //Метод запису результатів гри, повертає підсумок якщо серія завершилася
+    //standings - загальні очки гравців, що залишилися в кімнаті
+    pub fn record(&mut self, game: Losers, mut standings: Vec<Standing>) -> Option<SeriesOver> {
+        if self.target == Target::Single { return None }
+        self.games.push(game);
+        let over = match self.target {
+            Target::Games(games) => self.games.len() >= games as usize,
+            Target::Points(points) => standings.iter().any(|standing| standing.points >= points),
+            Target::Single => false,
+        };
+        if !over { return None }
+        let last = self.games.last()?;
+        let place = |id: &Uuid| last.iter().position(|loser| loser.id() == id).unwrap_or(usize::MAX);
+        //при однаковій кількості очків вище той, хто вибув раніше в останній грі
+        standings.sort_by(|a, b| b.points.cmp(&a.points).then_with(|| place(&a.id).cmp(&place(&b.id))));
+        let winner = standings.first()?.id;
+        Some(SeriesOver { winner, standings, games: std::mem::take(&mut self.games) })
+    }
+}
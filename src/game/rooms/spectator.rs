@@ -0,0 +1,66 @@
+use std::{borrow::Borrow, hash::Hash, sync::Arc, time::Duration};
+use serde::{ser::SerializeStruct, Serialize};
+use sea_orm::prelude::Uuid;
+use tokio::sync::{ RwLock, broadcast::Sender };
+use crate::{ gateway::payloads::Event, runtime_storage::DataTable };
+
+pub type Spectators = Arc<RwLock<DataTable<Spectator>>>; //Посилання на таблицю глядачів, спільне для кімнати та її гри
+
+#[derive(Debug, Clone)]
+pub struct Spectator { //Структура, що описує глядача кімнати
+    pub id: Uuid, //Ідентифікатор
+    pub sender: Sender<Event>, //Надсилач
+}
+
+impl Spectator {
+    pub fn new(id: Uuid, sender: Sender<Event>) -> Self { //Конструктор
+        Self { id, sender }
+    }
+}
+
+pub fn announce(spectators: &Spectators, content: Event, delay: u64) { //Оголошення повідомлення глядачам із затримкою в секундах
+    let spectators = spectators.clone();
+    tokio::spawn(async move {
+        if delay > 0 { tokio::time::sleep(Duration::from_secs(delay)).await; }
+        for spectator in spectators.read().await.iter() {
+            let _ = spectator.sender.send(content.clone());
+        }
+    });
+}
+
+pub fn send(sender: Sender<Event>, content: Event, delay: u64) { //Надсилання повідомлення одному глядачу із затримкою в секундах
+    tokio::spawn(async move {
+        if delay > 0 { tokio::time::sleep(Duration::from_secs(delay)).await; }
+        let _ = sender.send(content);
+    });
+}
+
+impl Serialize for Spectator { //Серіалізується тільки ідентифікатор
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer {
+        let mut state = serializer.serialize_struct("Spectator", 1)?;
+        state.serialize_field("id", &self.id)?;
+        state.end()
+    }
+}
+
+impl Eq for Spectator {} //Eq для Spectator за замовченням
+
+impl PartialEq for Spectator { //Порівняння тільки за полем id
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Hash for Spectator { //Хешування за полем id
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl Borrow<Uuid> for Spectator { //повернути поле id при запиті показника типу Uuid
+    fn borrow(&self) -> &Uuid {
+        &self.id
+    }
+}
@@ -0,0 +1,72 @@
+use flate2::{ Compress, FlushCompress };
+use poem::web::websocket::Message;
+use serde::Deserialize;
+use super::payloads::{ Event, Request };
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding { //Список кодувань повідомлень gateway
+    #[default]
+    Json, //текстові повідомлення JSON
+    Msgpack, //бінарні повідомлення MessagePack з тією ж структурою
+}
+
+impl Encoding {
+    pub fn encode(&self, event: Event) -> Message { //Перетворення події у повідомлення обраного кодування
+        match self {
+            Encoding::Json => Message::Text(event.to_string()),
+            Encoding::Msgpack => Message::Binary(rmp_serde::to_vec_named(&event).unwrap_or_default()), //поля структур кодуються за назвами, як у JSON
+        }
+    }
+
+    pub fn decode(&self, msg: Message) -> Option<Result<Request, String>> { //Декодування повідомлення клієнта, None якщо повідомлення не містить даних
+        match (self, msg) {
+            (_, Message::Text(text)) => Some(Request::from_json_str(&text).map_err(|e| e.to_string())), //текст завжди JSON
+            (Encoding::Msgpack, Message::Binary(bytes)) => Some(Request::from_msgpack_slice(&bytes).map_err(|e| e.to_string())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compression { //Список режимів стиснення вихідних повідомлень
+    ZlibStream, //один zlib потік на з'єднання, кожне повідомлення завершується SYNC_FLUSH
+}
+
+impl Compression {
+    pub fn compressor(&self) -> Compressor { //Створення стану стиснення для нового з'єднання
+        match self {
+            Compression::ZlibStream => Compressor(Compress::new(flate2::Compression::default(), true)),
+        }
+    }
+}
+
+pub struct Compressor(Compress); //Стан zlib потоку, спільний для всих повідомлень з'єднання
+
+impl Compressor {
+    pub fn compress(&mut self, msg: Message) -> Message { //Стиснення повідомлення у бінарне, клієнт розпаковує його тим самим потоком
+        let data = match msg {
+            Message::Text(text) => text.into_bytes(),
+            Message::Binary(bytes) => bytes,
+            other => return other,
+        };
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        let mut input = data.as_slice();
+        loop {
+            if output.len() == output.capacity() { output.reserve(64) } //місце для решти стиснених даних
+            let before = self.0.total_in();
+            if self.0.compress_vec(input, &mut output, FlushCompress::Sync).is_err() { break }
+            input = &input[(self.0.total_in() - before) as usize..];
+            if input.is_empty() && output.len() < output.capacity() { break } //усі дані стиснені та скинуті
+        }
+        Message::Binary(output)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GatewayQuery { //Структура, яка описує Query параметри підключення до gateway
+    #[serde(default)]
+    pub encoding: Encoding,
+    pub compress: Option<Compression>, //без параметра повідомлення не стискаються
+}
@@ -1,39 +1,64 @@
 
 use sea_orm::{prelude::Uuid, DatabaseConnection };
 use std::sync::Arc;
-use crate::database::queries;
+use crate::{
+    database::queries,
+    game::{ gameplay::Ok, rooms::{ self, Room } },
+    runtime_storage::Table,
+};
 use tokio::sync::{ broadcast::Sender, RwLock };
-use super::payloads::*;
+use super::{ payloads::*, sessions::Connection };
 
 //Receive
 
+async fn restore( //Функція яка переводить кімнату, гру та чергу сесії на новий надсилач
+    player: &mut super::sessions::User,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    sender: Sender<Event>,
+) {
+    let uuid = *player.uuid();
+    player.set_sender(sender.clone()); //назначити новий надсилач
+    let rooms = rooms_ptr.read().await; //замок на таблицю кімнат
+    //якщо кімната до якої був приєднаний гравець до виходу все ще існує
+    if let Some(room) = player.room.as_ref().and_then(|room_id| rooms.get(room_id).cloned()) {
+        let _ = room.player_update_sender(uuid, sender.clone()).await; //поновити сесію в кімнаті
+    }
+    //так само для кімнати за якою гравець спостерігає
+    if let Some(room) = player.spectating.as_ref().and_then(|room_id| rooms.get(room_id).cloned()) {
+        let _ = room.spectator_update_sender(uuid, sender.clone()).await;
+    }
+    drop(rooms); //відімкнути таблицю кімнат
+    queue_ptr.write().await.update_sender(&uuid, sender); //поновити сесію в черзі пошуку гри
+}
+
+async fn authorize(db: &DatabaseConnection, token: String) -> Result<Uuid, Error> { //Функція перевірки токену та доставання id акаунту
+    let token = Uuid::parse_str(token.as_str()).map_err(|_| Error::BadToken)?; //Парсинг токену в Uuid
+    queries::sessions::handle(db, token).await
+        .map_err(|_| Error::InvalidToken) //Перевірка токену на валідність, повернення помилки у разі невірного токену
+}
+
 pub async fn identify( //Функція яка ідентифікує акаунт за токеном, авторизує та відновлює сесію
     db: &DatabaseConnection,
     payload: Identify,
     players_ptr: &Arc<RwLock<crate::Players>>,
     rooms_ptr: &Arc<RwLock<crate::Rooms>>,
-    sender: Sender<String>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    connection: &Connection,
     store_in: &mut Option<Uuid>,
 ) -> Result<Payload, Error> {
-    let token = Uuid::parse_str(payload.token().as_str()).map_err(|_| Error::BadToken)?; //Парсинг токену в Uuid
-    let uuid = queries::sessions::handle(db, token).await
-        .map_err(|_| Error::InvalidToken)?; //Перевірка токену на валідність та доставання id акаунту, повернення помилки у разі невірного токену
+    let uuid = authorize(db, payload.token()).await?;
 
     let mut players = players_ptr.write().await; //замок на таблицю гравців
     let player = if let Some(player) = players.get(&uuid).cloned().as_mut() { //якщо гравець вже авторизований
-        player.set_sender(sender.clone()); //назначити новий надсилач
-        let rooms = rooms_ptr.read().await; //замок на таблицю кімнат
-        //якщо кімната до якої був приєднаний гравець до виходу все ще існує
-        if let Some(room) = player.room.as_ref().and_then(|room_id| rooms.get(room_id).cloned()) {
-            let _ = room.player_update_sender(uuid, sender.clone()).await; //поновити сесію в кімнаті
-        }
-        drop(rooms); //відімкнути таблицю кімнат
+        player.replay = connection.replay.clone(); //нумерація подій починається з буфера нового з'єднання
+        restore(player, rooms_ptr, queue_ptr, connection.sender.clone()).await;
         player.to_owned() //клонування гравця з таблиці та повернути у якості результату замикання
     } else { //інакше
         let account = queries::accounts::by_uuid(uuid).one(db).await
             .map_err(|_| Error::InternalServerError)?
             .ok_or(Error::InvalidToken)?; //Дістати акаунт за Uuid
-        let player = super::sessions::User::from_account(account, sender); //та перетворити у сесію
+        let player = super::sessions::User::from_account(account, connection.sender.clone(), connection.replay.clone()); //та перетворити у сесію
         player
     };
     players.replace(player.clone()); //Помістити нове значення у таблицю, замінивши старе якщо воно є
@@ -41,6 +66,274 @@ pub async fn identify( //Функція яка ідентифікує акаун
     Ok(Payload::Ready(player.to_owned()))
 }
 
+pub async fn resume( //Функція яка відновлює наявну сесію та повторює пропущені клієнтом події
+    db: &DatabaseConnection,
+    payload: Resume,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    connection: &Connection,
+    store_in: &mut Option<Uuid>,
+) -> Result<Payload, Error> {
+    let uuid = authorize(db, payload.token).await?;
+
+    let mut players = players_ptr.write().await; //замок на таблицю гравців
+    let Some(mut player) = players.get(&uuid).cloned() else { return Ok(Payload::InvalidSession {}) }; //сесія вже видалена
+    if Arc::ptr_eq(&player.replay, &connection.replay) { //сесія вже належить цьому з'єднанню, відновлювати нічого
+        return Err(Error::Conflict(String::from("Session is already attached to this connection")))
+    }
+    {
+        let mut old = player.replay.lock().map_err(|_| Error::InternalServerError)?;
+        let Some(events) = old.missed(payload.last_seq) else { return Ok(Payload::InvalidSession {}) }; //буфер не сягає last_seq
+        let mut new = connection.replay.lock().map_err(|_| Error::InternalServerError)?;
+        *new = old.detach(); //нове з'єднання продовжує нумерацію сесії
+        for event in events { //пропущені події надсилаються до будь-яких нових
+            let _ = connection.missed.send(event);
+        }
+    }
+    player.replay = connection.replay.clone();
+    restore(&mut player, rooms_ptr, queue_ptr, connection.sender.clone()).await;
+    players.replace(player);
+    *store_in = Some(uuid);
+    Ok(Payload::Resumed {})
+}
+
+pub async fn queue_join( //Функція входу в чергу пошуку гри
+    db: &DatabaseConnection,
+    payload: QueueJoin,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let user_id = user_id.ok_or(Error::Unauthorized)?; //тільки для ідентифікованих сесій
+    let player = players_ptr.read().await.get(&user_id).cloned().ok_or(Error::Unauthorized)?;
+    if player.room.is_some() || player.spectating.is_some() { return Err(Error::Forbidden) } //гравець вже в кімнаті
+    let account = queries::accounts::by_uuid(user_id).one(db).await
+        .map_err(|_| Error::InternalServerError)?
+        .ok_or(Error::NotFound)?; //рейтинг гравця для підбору
+    queue_ptr.write().await.join(user_id, player.sender.clone(), account.rating, payload.players())
+        .map(Payload::QueueStatus)
+        .map_err(|_| Error::BadArgument(String::from("players must be between 2 and 8")))
+}
+
+pub async fn queue_leave(queue_ptr: &Arc<RwLock<crate::Queue>>, user_id: &Option<Uuid>) -> Result<Payload, Error> { //Функція виходу з черги пошуку гри
+    let user_id = user_id.ok_or(Error::Unauthorized)?;
+    queue_ptr.write().await.leave(&user_id).map(Payload::QueueStatus).ok_or(Error::NotFound)
+}
+
+pub async fn lobby_subscribe( //Функція підписки на лобі, повертає поточний список публічних кімнат
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    lobby_ptr: &Arc<RwLock<crate::Lobby>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let mut players = players_ptr.write().await;
+    let mut player = session(players.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let rooms = rooms_ptr.read().await; //список формується під замком, щоб жодна подія не загубилася між ним та підпискою
+    let list = rooms.iter().filter(|room| room.is_public).map(|room| rooms::Partial(room.clone())).collect();
+    lobby_ptr.write().await.subscribe(*player.uuid(), player.sender.clone());
+    player.lobby = true;
+    players.replace(player);
+    Ok(Payload::LobbyRooms(list))
+}
+
+pub async fn lobby_restore(players_ptr: &Arc<RwLock<crate::Players>>, lobby_ptr: &Arc<RwLock<crate::Lobby>>, user_id: &Option<Uuid>) { //Поновлення підписки на лобі після ідентифікації чи відновлення сесії
+    let Some(user_id) = user_id else { return };
+    let players = players_ptr.read().await;
+    if let Some(player) = players.get(user_id).filter(|player| player.lobby) {
+        lobby_ptr.write().await.subscribe(*user_id, player.sender.clone()); //сесія вже переведена на новий надсилач
+    }
+}
+
+pub async fn lobby_unsubscribe( //Функція відписки від лобі
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    lobby_ptr: &Arc<RwLock<crate::Lobby>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let mut players = players_ptr.write().await;
+    let mut player = session(players.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    if !lobby_ptr.write().await.unsubscribe(player.uuid()) { return Err(Error::NotFound) }
+    player.lobby = false;
+    players.replace(player);
+    Ok(Payload::Ack {})
+}
+
+pub async fn heartbeat_ack(heartbeat: Heartbeat, players_ptr: &Arc<RwLock<crate::Players>>, user_id: &Option<Uuid>) { //Запис затримки з'єднання у сесію
+    let Some(user_id) = user_id else { return }; //до ідентифікації сесії немає
+    let mut players = players_ptr.write().await;
+    if let Some(mut player) = players.get(user_id).cloned() {
+        player.latency = Some(heartbeat.elapsed());
+        players.replace(player);
+    }
+}
+
+fn session(player: Option<&super::sessions::User>) -> Result<super::sessions::User, Error> { //Сесія ідентифікованого користувача
+    player.cloned().ok_or(Error::Unauthorized)
+}
+
+pub async fn room_setup( //Функція створення кімнати, відповідає POST /api/rooms
+    payload: RoomSetup,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    lobby_ptr: &Arc<RwLock<crate::Lobby>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let mut players = players_ptr.write().await;
+    let mut player = session(players.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    if player.room.is_some() || player.spectating.is_some() { return Err(Error::Forbidden) } //гравець вже в кімнаті
+    let mut rooms = rooms_ptr.write().await;
+    let mut room = Room::create(payload.name, payload.is_public, payload.password, *player.uuid(), payload.max_players, payload.rules, player.sender.clone()).await?;
+    room.set_lobby(lobby_ptr.read().await.feed());
+    room.allow_spectators = payload.allow_spectators;
+    room.set_spectator_delay(payload.spectator_delay)?;
+    room.set_auto_start(payload.auto_start)?;
+    room.set_series(payload.series).await?;
+    while rooms.get(&room.clone()).is_some() { //доки в таблиці вже існує кімната з таким ідентифікатором
+        room.regenerate_id()
+    };
+    rooms.insert(room.clone());
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
+    player.room = Some(room.id().clone()); //задання кімнати для відновлення сесії
+    players.replace(player);
+    Ok(Payload::RoomCreate(room))
+}
+
+pub async fn room_join( //Функція приєднання до кімнати, відповідає POST /api/rooms/:id/join
+    payload: RoomJoin,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    queue_ptr: &Arc<RwLock<crate::Queue>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let mut players = players_ptr.write().await;
+    let mut player = session(players.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let rooms = rooms_ptr.read().await;
+    let room = rooms.get(&payload.room).cloned().ok_or(Error::NotFound)?;
+    if player.spectating.is_some() { return Err(Error::Forbidden) } //глядач повинен припинити спостереження
+    if let Some(room_id) = &player.room { //гравець вже в кімнаті
+        return if *room_id == payload.room { Ok(Payload::RoomCreate(room)) } else { Err(Error::Forbidden) }
+    }
+    room.join(payload.password, *player.uuid(), player.sender.clone()).await?;
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
+    player.room = Some(room.id().clone());
+    players.replace(player);
+    Ok(Payload::RoomCreate(room))
+}
+
+pub async fn room_leave( //Функція виходу з кімнати, відповідає POST /api/rooms/:id/leave
+    db: &Arc<DatabaseConnection>,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let mut players = players_ptr.write().await;
+    let mut player = session(players.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let mut rooms = rooms_ptr.write().await;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let mut room = rooms.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    match room.leave(*player.uuid()).await {
+        Err(rooms::Error::CantAssignNewOwner) => { rooms.remove(&room); }, //не залишилося гравців, видалити кімнату
+        Ok(true) => { rooms.replace(room.clone()); }, //власник змінився, оновити кімнату
+        Ok(false) => {},
+        Err(e) => return Err(e.into()),
+    }
+    room.start_countdown(db.clone(), rooms_ptr.clone()).await; //решта гравців може бути готовою
+    player.room = None;
+    players.replace(player);
+    Ok(Payload::Ack {})
+}
+
+pub async fn room_ready( //Функція перемикання готовності, відповідає POST /api/rooms/:id/ready
+    db: &Arc<DatabaseConnection>,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let player = session(players_ptr.read().await.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let room = rooms_ptr.read().await.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    room.player_switch_ready(*player.uuid()).await?;
+    room.start_countdown(db.clone(), rooms_ptr.clone()).await; //відлік до автоматичного початку гри, якщо усі готові
+    Ok(Payload::Ack {})
+}
+
+pub async fn room_edit( //Функція оновлення параметрів кімнати, відповідає PATCH /api/rooms/:id
+    db: &Arc<DatabaseConnection>,
+    payload: RoomEdit,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let player = session(players_ptr.read().await.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let mut rooms = rooms_ptr.write().await;
+    let mut room = rooms.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    if *room.owner() != *player.uuid() { return Err(Error::Forbidden) } //тільки власник може оновлювати кімнату
+    //при першій помилці кімната не змінюється
+    if let Some(value) = payload.name { room.set_name(value)? }
+    if let Some(value) = payload.is_public { room.is_public = value }
+    if let Some(value) = payload.password { room.set_password(Some(value))? }
+    if let Some(value) = payload.owner { room.set_owner(value)? }
+    if let Some(value) = payload.max_players { room.set_max_players(value)? }
+    if let Some(value) = payload.rules { room.set_rules(value)? }
+    if let Some(value) = payload.allow_spectators { room.allow_spectators = value }
+    if let Some(value) = payload.spectator_delay { room.set_spectator_delay(value)? }
+    if let Some(value) = payload.auto_start { room.set_auto_start(value)? }
+    if let Some(value) = payload.series { room.set_series(value).await? } //останнім, бо серія змінюється одразу для усіх копій кімнати
+    rooms.replace(room.clone());
+    room.start_countdown(db.clone(), rooms_ptr.clone()).await; //відлік міг бути щойно увімкнений
+    Ok(Payload::Ack {})
+}
+
+pub async fn room_message( //Функція надсилання повідомлення чи реакції в чат кімнати
+    content: rooms::chat::Content,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let player = session(players_ptr.read().await.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let room = rooms_ptr.read().await.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    room.send_message(*player.uuid(), content).await?;
+    Ok(Payload::Ack {})
+}
+
+pub async fn game_start( //Функція створення нової гри, відповідає POST /api/rooms/:id/game
+    db: &Arc<DatabaseConnection>,
+    payload: GameStart,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let player = session(players_ptr.read().await.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let mut rooms = rooms_ptr.write().await;
+    let mut room = rooms.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    if *room.owner() != *player.uuid() { return Err(Error::Forbidden) } //тільки власник може почати гру
+    room.start_game(payload.seed).await?;
+    rooms.replace(room.clone());
+    room.watch_turn(db.clone()); //запуск таймера першого ходу
+    Ok(Payload::Ack {})
+}
+
+pub async fn game_play( //Функція ходу у грі, відповідає POST /api/rooms/:id/game/play/:card_id
+    db: &Arc<DatabaseConnection>,
+    payload: GamePlay,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<crate::Rooms>>,
+    user_id: &Option<Uuid>,
+) -> Result<Payload, Error> {
+    let player = session(players_ptr.read().await.get(&user_id.ok_or(Error::Unauthorized)?))?;
+    let room_id = player.room.clone().ok_or(Error::NotFound)?;
+    let room = rooms_ptr.read().await.get(&room_id).cloned().ok_or(Error::NotFound)?;
+    match room.play_game(*player.uuid(), payload.card).await? {
+        Ok::GameOver(players) => room.save_results(db.as_ref(), &players).await, //зберегти результати гри
+        _ => room.watch_turn(db.clone()), //запуск таймера нового ходу
+    }
+    Ok(Payload::Ack {})
+}
+
 pub trait TableEvents { //Ознака яка задає методи для надсилання подій всередині об'єкту
     fn insert(&self);
     fn update(&self);
@@ -0,0 +1,75 @@
+use std::{collections::{ HashMap, HashSet }, sync::Arc};
+use sea_orm::prelude::Uuid;
+use tokio::sync::{ RwLock, broadcast::Sender, mpsc::{ self, UnboundedReceiver, UnboundedSender } };
+use crate::game::rooms::{ Partial, Room };
+use super::payloads::{ Event, Payload };
+
+pub type LobbyFeed = UnboundedSender<Change>; //Надсилач змін кімнат, спільний для усіх кімнат
+
+#[derive(Debug)]
+pub enum Change { //Список змін кімнат, які оголошуються підписникам лобі
+    Update(String, Event), //публічна кімната створена чи змінена
+    Delete(String, Event), //кімната видалена або стала приватною
+}
+
+#[derive(Debug)]
+pub struct Lobby { //Структура, що описує підписників на список публічних кімнат
+    subscribers: HashMap<Uuid, Sender<Event>>, //надсилачі підписаних гравців
+    listed: HashSet<String>, //кімнати, про які підписники вже знають
+    feed: LobbyFeed, //надсилач змін, які застосовуються по черзі в run
+}
+
+impl Lobby {
+    pub fn new() -> (Self, UnboundedReceiver<Change>) { //Конструктор порожнього лобі та отримувача його змін
+        let (feed, changes) = mpsc::unbounded_channel();
+        (Self { subscribers: HashMap::new(), listed: HashSet::new(), feed }, changes)
+    }
+
+    pub fn feed(&self) -> LobbyFeed { //Геттер надсилача змін для нової кімнати
+        self.feed.clone()
+    }
+
+    pub fn subscribe(&mut self, id: Uuid, sender: Sender<Event>) { //Підписка гравця, повторна підписка оновлює надсилач
+        self.subscribers.insert(id, sender);
+    }
+
+    pub fn unsubscribe(&mut self, id: &Uuid) -> bool { //Відписка гравця, false якщо гравець не був підписаний
+        self.subscribers.remove(id).is_some()
+    }
+
+    fn announce(&self, content: Event) { //Оголошення події усім підписникам
+        for sender in self.subscribers.values() {
+            let _ = sender.send(content.clone());
+        }
+    }
+
+    fn apply(&mut self, change: Change) { //Застосування зміни до списку та оголошення її підписникам
+        match change {
+            Change::Update(id, content) => {
+                self.listed.insert(id);
+                self.announce(content);
+            },
+            Change::Delete(id, content) => if self.listed.remove(&id) { //кімнати, якої немає у списку, підписники не бачили
+                self.announce(content);
+            },
+        }
+    }
+}
+
+pub async fn run(lobby_ptr: Arc<RwLock<Lobby>>, mut changes: UnboundedReceiver<Change>) { //Фоновий процес застосування змін у порядку їх надсилання
+    while let Some(change) = changes.recv().await {
+        lobby_ptr.write().await.apply(change);
+    }
+}
+
+//Зміни надсилають TableEvents кімнат під замком таблиці кімнат, тому вони формуються одразу, а порядок зберігає канал
+pub fn update(feed: &LobbyFeed, room: &Room) { //Оголошення створення чи зміни кімнати, приватні кімнати не розкриваються
+    if !room.is_public { return delete(feed, room) } //кімната стала приватною, прибрати її зі списку
+    let content = Payload::LobbyRoomUpdate(Partial(room.clone())).to_event();
+    let _ = feed.send(Change::Update(room.id().clone(), content));
+}
+
+pub fn delete(feed: &LobbyFeed, room: &Room) { //Оголошення видалення кімнати, якщо вона була в списку
+    let content = Payload::LobbyRoomDelete(room.id().clone()).to_event();
+    let _ = feed.send(Change::Delete(room.id().clone(), content));
+}
@@ -1,20 +1,27 @@
+pub mod encoding;
+pub mod lobby;
 pub mod payloads;
 pub mod events;
 pub mod sessions;
 
 use futures_util::SinkExt;
 use poem::{
-    handler, http::StatusCode, web::{ websocket::{Message, WebSocket }, Data }, IntoResponse
+    handler, http::StatusCode, web::{ websocket::WebSocket, Data, Query }, IntoResponse
 };
-use serde_json;
 use sea_orm::{prelude::Uuid, DatabaseConnection};
 use std::{sync::Arc, time::Duration};
-use tokio::sync::{ broadcast, RwLock };
-use tokio::time::sleep;
+use tokio::sync::{ broadcast, mpsc, oneshot, RwLock };
+use tokio::time::{ sleep, Instant };
 use futures_util::StreamExt;
 use payloads::*;
+use encoding::GatewayQuery;
 use crate::{game::rooms, runtime_storage::Table};
 
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60); //Інтервал перевірки з'єднання
+const CHANNEL_CAPACITY: usize = 128; //Кількість подій, які можуть очікувати надсилання
+const HEARTBEAT_GRACE: Duration = Duration::from_secs(30); //Додатковий час на відповідь, після якого з'єднання вважається мертвим
+const RESUME_TIMEOUT: Duration = Duration::from_secs(60); //Час після розриву з'єднання, протягом якого сесію можна відновити
+
 fn unwrap_event(event: Result<Payload, Error>) -> Payload { //Розгортач результатів подій
     match event {
         Ok(payload) => payload,
@@ -22,63 +29,154 @@ fn unwrap_event(event: Result<Payload, Error>) -> Payload { //Розгортач
     }
 }
 
+fn is_filtered(event: &Event) -> bool { //Події, JSON текст яких містить "./", не надсилаються клієнту за будь-якого кодування
+    event.to_string().contains("./")
+}
+
+fn sequence(replay: &sessions::ReplayBuffer, event: Event) -> Event { //Нумерація події перед надсиланням
+    match replay.lock() {
+        Ok(mut replay) => replay.push(event),
+        Err(_) => event,
+    }
+}
+
 #[handler]
 pub async fn gateway(
     ws: WebSocket,
+    Query(query): Query<GatewayQuery>,
     db: Data<&Arc<DatabaseConnection>>,
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<crate::Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+    lobby_ptr: Data<&Arc<RwLock<crate::Lobby>>>,
 ) -> Result<impl IntoResponse, StatusCode> {
     let db = db.to_owned(); //Доставання значення з показника
     let players = players_ptr.to_owned(); //Доставання замка таблиці гравців з показника
     let rooms = rooms_ptr.to_owned(); //Доставання замка таблиці кімнат з показника
-    let (sender, mut receiver) = broadcast::channel::<String>(12); //створення нового каналу
+    let queue = queue_ptr.to_owned(); //Доставання замка черги пошуку гри з показника
+    let lobby = lobby_ptr.to_owned(); //Доставання замка лобі з показника
+    let encoding = query.encoding; //кодування, обране клієнтом при підключенні
+    let mut compressor = query.compress.map(|compression| compression.compressor()); //стиснення, обране клієнтом при підключенні
+    let (sender, mut receiver) = broadcast::channel::<Event>(CHANNEL_CAPACITY); //створення нового каналу
+    let (missed, mut missed_receiver) = mpsc::unbounded_channel::<Event>(); //канал для повторення пропущених подій
+    let replay = sessions::Replay::new(); //буфер подій цього з'єднання
+    let connection = sessions::Connection { sender: sender.clone(), replay: replay.clone(), missed };
     //let mut receivers = sender.subscribe();
     Ok(
         ws.on_upgrade(move |mut socket| async move { //при перетворенні http з'єднання в gateway
             let (mut sink, mut stream) = socket.split(); //розділити з'єднання на вхідний та вихідний потік
-            let hello = Payload::Hello( Hello::new(60) ); //Привітальне повідомлення
-            let _ = sink.send(Message::Text(serde_json::to_string(&hello).unwrap_or_default())).await; //Надсилання прив. повідомлення
+            let hello = Payload::Hello( Hello::new(HEARTBEAT_INTERVAL.as_secs()) ); //Привітальне повідомлення
+            let mut hello = encoding.encode(hello.to_event());
+            if let Some(compressor) = compressor.as_mut() { hello = compressor.compress(hello) } //привітання починає zlib потік
+            let _ = sink.send(hello).await; //Надсилання прив. повідомлення
+
+            let (close, mut closed) = oneshot::channel::<()>(); //закриття close означає кінець з'єднання для потоку надсилання
 
             //Створення нового потоку для вхідних значень
             tokio::spawn(async move {
+                let _close = close; //закривається разом з цим потоком
+                let mut last_seen = Instant::now(); //час останнього повідомлення клієнта
+                let mut heartbeat = tokio::time::interval_at(Instant::now() + HEARTBEAT_INTERVAL, HEARTBEAT_INTERVAL);
                 let mut user_id: Option<Uuid> = None; //id користувача у цьому замиканні 
+                let db_ptr = db.clone(); //Розумний вказівник на з'єднання БД для таймерів ходу
                 let db = db.as_ref(); //Показник на з'єднання БД
                 //let mut rooms = rooms.write().unwrap();
-                while let Some(Ok(msg)) = stream.next().await { //Доки надходять повідомлення
-                    if let Message::Text(text) = msg { //Якщо повідомлення містить текст
-                        let request = serde_json::from_str(&text); //Десеріалізувати з JSON
+                loop {
+                    let msg = tokio::select! {
+                        msg = stream.next() => match msg { //Доки надходять повідомлення
+                            Some(Ok(msg)) => msg,
+                            _ => break,
+                        },
+                        _ = heartbeat.tick() => {
+                            if last_seen.elapsed() > HEARTBEAT_INTERVAL + HEARTBEAT_GRACE { break } //клієнт пропустив перевірку, з'єднання мертве
+                            let _ = sender.send(Payload::Heartbeat(Heartbeat::now()).to_event()); //перевірка з'єднання з боку сервера
+                            continue
+                        },
+                    };
+                    last_seen = Instant::now(); //будь-яке повідомлення підтверджує, що з'єднання живе
+                    if let Some(request) = encoding.decode(msg) { //Якщо повідомлення містить дані
+                        //Десеріалізувати з обраного кодування
+                        let nonce = request.as_ref().ok().and_then(|request| request.nonce.clone()); //nonce повертається у відповіді
 
                         let payload = unwrap_event( //Розгорнути результат наступної події
                             if let Ok(request) = request { //якщо десеріалізація пройшла успішно
-                                match request {
-                                    Payload::Identify(payload) => //ідентифікація та авторизація акаунта за наданим токеном
-                                        events::identify(db, payload, &players, &rooms.clone(), sender.clone(), &mut user_id).await,
+                                match request.payload {
+                                    Payload::Heartbeat(payload) => //перевірка з'єднання з боку клієнта
+                                        Ok(Payload::HeartbeatAck(payload)),
+                                    Payload::HeartbeatAck(payload) => { //відповідь на перевірку сервера, не потребує відповіді
+                                        events::heartbeat_ack(payload, &players, &user_id).await;
+                                        continue
+                                    },
+                                    Payload::Identify(payload) => { //ідентифікація та авторизація акаунта за наданим токеном
+                                        let result = events::identify(db, payload, &players, &rooms.clone(), &queue, &connection, &mut user_id).await;
+                                        events::lobby_restore(&players, &lobby, &user_id).await;
+                                        result
+                                    },
+                                    Payload::Resume(payload) => { //відновлення сесії з повторенням пропущених подій
+                                        let result = events::resume(db, payload, &players, &rooms, &queue, &connection, &mut user_id).await;
+                                        events::lobby_restore(&players, &lobby, &user_id).await;
+                                        result
+                                    },
+                                    Payload::QueueJoin(payload) => //вхід в чергу пошуку гри
+                                        events::queue_join(db, payload, &players, &queue, &user_id).await,
+                                    Payload::QueueLeave => //вихід з черги пошуку гри
+                                        events::queue_leave(&queue, &user_id).await,
+                                    Payload::LobbySubscribe => //підписка на лобі
+                                        events::lobby_subscribe(&players, &rooms, &lobby, &user_id).await,
+                                    Payload::LobbyUnsubscribe => //відписка від лобі
+                                        events::lobby_unsubscribe(&players, &lobby, &user_id).await,
+                                    Payload::RoomSetup(payload) => //створення кімнати
+                                        events::room_setup(payload, &players, &rooms, &queue, &lobby, &user_id).await,
+                                    Payload::RoomJoin(payload) => //приєднання до кімнати
+                                        events::room_join(payload, &players, &rooms, &queue, &user_id).await,
+                                    Payload::RoomLeave => //вихід з кімнати
+                                        events::room_leave(&db_ptr, &players, &rooms, &user_id).await,
+                                    Payload::RoomReady => //перемикання готовності
+                                        events::room_ready(&db_ptr, &players, &rooms, &user_id).await,
+                                    Payload::RoomEdit(payload) => //оновлення параметрів кімнати
+                                        events::room_edit(&db_ptr, payload, &players, &rooms, &user_id).await,
+                                    Payload::RoomMessage(payload) => //повідомлення в чат кімнати
+                                        events::room_message(rooms::chat::Content::Text(payload.content), &players, &rooms, &user_id).await,
+                                    Payload::RoomEmote(emote) => //реакція в чат кімнати
+                                        events::room_message(rooms::chat::Content::Emote(emote), &players, &rooms, &user_id).await,
+                                    Payload::GameStart(payload) => //створення нової гри
+                                        events::game_start(&db_ptr, payload, &players, &rooms, &user_id).await,
+                                    Payload::GamePlay(payload) => //хід у грі
+                                        events::game_play(&db_ptr, payload, &players, &rooms, &user_id).await,
                                     _ => {         // всі інші повідомлення - відмова
                                         Ok(Payload::Error( Error::Declined )) 
                                     },
                                 }
                             } else { Err(Error::BadRequest(request.unwrap_err().to_string())) } //якщо десеріалізація повернула помилку, повернути її
                         );
-                        let _ = sender.send(payload.to_json_string()); //Надіслати payload клієнту
+                        let _ = sender.send(Reply { nonce, payload }.to_event()); //Надіслати відповідь клієнту
                     }
                 }
                 //якщо з'єднання закрилося
                 if let Some(user_id) = user_id { //якщо користувач авторизувався
-                    let _ = sleep(Duration::from_secs(60)).await; //зачекати 60 секунд
+                    let _ = sleep(RESUME_TIMEOUT).await; //зачекати, поки сесію можна відновити
                     let mut players = players.write().await; //замок на таблицю гравців
                     let disconnect = if let Some(player) = players.get(&user_id) { 
                         if sender.same_channel(&player.sender) { Some(player.clone()) } else { None } 
                         //disconnect = якщо сесія все ще має той самий надсилач, тобто вона не було відновлена
                     } else { None };
                     if let Some(player) = disconnect { //якщо disconnect 
+                        queue.write().await.leave(&user_id); //видалити з черги пошуку гри
+                        lobby.write().await.unsubscribe(&user_id); //відписати від лобі
+                        let rooms_ptr = rooms.clone(); //показник для відліку до автоматичного початку гри
                         let mut rooms = rooms.write().await; //замок на таблицю кімнат
                         if let Some(mut room) = player.room.and_then(|room_id| rooms.get(&room_id).cloned()) { //дістати кімнату
                             match room.leave(user_id).await { // викликати метод виходу гравця з кімнати
                                 Err(rooms::Error::CantAssignNewOwner) => { rooms.remove(&room); }, //видалити кімнату якщо вона пуста
-                                Ok(_) | Err(_) => { rooms.replace(room); }, //інашке оновити кімнату
+                                Ok(_) | Err(_) => {
+                                    rooms.replace(room.clone()); //інашке оновити кімнату
+                                    room.start_countdown(db_ptr.clone(), rooms_ptr).await; //решта гравців може бути готовою
+                                },
                             }
                         }
+                        if let Some(room) = player.spectating.and_then(|room_id| rooms.get(&room_id).cloned()) { //припинити спостереження
+                            let _ = room.stop_spectating(user_id).await;
+                        }
                         players.remove(&user_id); //видалити сесію
                     }
                 }
@@ -87,13 +185,47 @@ pub async fn gateway(
             //створити новий поток надсилання значень
             tokio::spawn(async move {
                 //доки канал відкритий
-                while let Ok(text) = receiver.recv().await {
-                    if text.contains("./") { continue };
-                    let msg = Message::text(text);
+                loop {
+                    let event = tokio::select! {
+                        biased;
+                        Some(event) = missed_receiver.recv() => event, //пропущені події вже пронумеровані, тому надсилаються першими
+                        event = receiver.recv() => match event {
+                            Ok(event) if is_filtered(&event) => continue,
+                            Ok(event) => sequence(&replay, event),
+                            Err(broadcast::error::RecvError::Lagged(_)) => { //частина подій втрачена, відновлення неможливе
+                                if let Ok(mut replay) = replay.lock() { replay.clear() }
+                                sequence(&replay, Payload::InvalidSession {}.to_event())
+                            },
+                            Err(_) => break,
+                        },
+                        _ = &mut closed => break, //потік вхідних значень завершився
+                    };
+                    let mut msg = encoding.encode(event); //закодувати обраним клієнтом кодуванням
+                    if let Some(compressor) = compressor.as_mut() { msg = compressor.compress(msg) } //та стиснути
                     if let Err(_) = sink.send(msg).await { //надіслати вхідний текст клієнту
                         break;
                     }
                 }
+                let _ = sink.close().await; //закрити з'єднання
+                //сесію ще можна відновити, тому події, надіслані після розриву, продовжують нумеруватися в буфері
+                let deadline = sleep(RESUME_TIMEOUT);
+                tokio::pin!(deadline);
+                loop {
+                    tokio::select! {
+                        event = receiver.recv() => match event {
+                            Ok(event) if is_filtered(&event) => continue,
+                            Ok(event) => match replay.lock() {
+                                Ok(mut buffer) if !buffer.is_detached() => { buffer.push(event); },
+                                _ => break, //буфер вже переданий новому з'єднанню
+                            },
+                            Err(broadcast::error::RecvError::Lagged(_)) => { //частина подій втрачена, відновлення неможливе
+                                if let Ok(mut replay) = replay.lock() { replay.clear() }
+                            },
+                            Err(_) => break,
+                        },
+                        _ = &mut deadline => break, //сесія вже видалена
+                    }
+                }
             });
 
         })
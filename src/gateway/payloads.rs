@@ -1,7 +1,9 @@
 use sea_orm::prelude::Uuid;
 use serde::{ Serialize, Deserialize };
 use serde_json;
-use crate::game::{self, gameplay::player::Losers};
+use crate::game::{self, gameplay::{ events::*, player::Losers, rules::RuleSet, Direction }, matchmaking::QueueStatus};
+
+pub type Event = serde_json::Value; //Подія в каналі з'єднання, кодується обраним клієнтом кодуванням тільки при надсиланні
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Payload { //Список усих можливих подій
@@ -23,25 +25,110 @@ pub enum Payload { //Список усих можливих подій
     #[serde(skip_deserializing)]
     RoomDelete(String), //Подія видалення кімнати
     #[serde(skip_deserializing)]
-    GameStarted(game::gameplay::Game), //Подія створення нової гри
+    LobbyRooms(Vec<game::rooms::Partial>), //Подія списку публічних кімнат при підписці на лобі
+    #[serde(skip_deserializing)]
+    LobbyRoomUpdate(game::rooms::Partial), //Подія створення чи зміни публічної кімнати
+    #[serde(skip_deserializing)]
+    LobbyRoomDelete(String), //Подія видалення кімнати зі списку лобі
+    #[serde(skip_deserializing)]
+    RoomMessageNew(game::rooms::chat::Message), //Подія нового повідомлення чи реакції в чаті кімнати
+    #[serde(skip_deserializing)]
+    RoomCountdown(u64), //Подія початку відліку до автоматичного початку гри, містить тривалість у секундах
+    #[serde(skip_deserializing)]
+    RoomCountdownCancel, //Подія скасування відліку
+    #[serde(skip_deserializing)]
+    GameStarted(Box<game::gameplay::Game>), //Подія створення нової гри, також знімок стану гри при відновленні сесії
+    #[serde(skip_deserializing)]
+    CardPlayed(CardPlayed), //Подія зіграної карти
+    #[serde(skip_deserializing)]
+    CardsDrawn(CardsDrawn), //Подія взяття карт з колоди
+    #[serde(skip_deserializing)]
+    TurnChanged(TurnChanged), //Подія передачі ходу
     #[serde(skip_deserializing)]
-    GameNewTurn(game::gameplay::Game), //Подія нового ходу гри
+    DirectionChanged(Direction), //Подія зміни напрямку ходів
+    #[serde(skip_deserializing)]
+    PlayerFinished(PlayerFinished), //Подія вибуття гравця з гри
+    #[serde(skip_deserializing)]
+    HandDelta(HandDelta), //Подія зміни карт відповідного гравця
     #[serde(skip_deserializing)]
     GamePlayerCards(Vec<game::gameplay::card::Card>), //Подія оголошення карт відповідного гравця
     #[serde(skip_deserializing)]
     GameOver(Losers), //Подія закінчення гри
+    #[serde(skip_deserializing)]
+    SeriesOver(game::rooms::series::SeriesOver), //Подія закінчення серії ігор з переможцем та результатами
     //From Server/Client
+    Heartbeat(Heartbeat), //Подія перевірки з'єднання, на яку інша сторона відповідає HeartbeatAck
+    HeartbeatAck(Heartbeat), //Подія відповіді на перевірку з'єднання з тією ж міткою часу
     Identify(Identify), //Подія ідентифікації та авторизації за токеном
+    Resume(Resume), //Подія відновлення сесії з повторенням пропущених подій
     #[serde(skip_deserializing)]
     Ready(super::sessions::User), //Подія окінчення ідентифікації
-    /*//From Client
-    RoomJoin(RoomJoin),
-    RoomLeave(String),*/
+    #[serde(skip_deserializing)]
+    QueueStatus(QueueStatus), //Подія статусу черги пошуку гри
+    #[serde(skip_deserializing)]
+    MatchFound(String), //Подія знайденої гри, містить ідентифікатор створеної кімнати
+    #[serde(skip_deserializing)]
+    Ack {}, //Подія успішного виконання команди клієнта, структурний варіант, бо Reply не може розгорнути варіант без даних
+    #[serde(skip_deserializing)]
+    Resumed {}, //Подія успішного відновлення сесії, надсилається після пропущених подій
+    #[serde(skip_deserializing)]
+    InvalidSession {}, //Подія неможливості відновлення сесії, клієнт повинен повторити Identify
+    //From Client
+    QueueJoin(QueueJoin), //Подія входу в чергу пошуку гри
+    QueueLeave, //Подія виходу з черги пошуку гри
+    LobbySubscribe, //Подія підписки на зміни списку публічних кімнат
+    LobbyUnsubscribe, //Подія відписки від змін списку публічних кімнат
+    RoomSetup(RoomSetup), //Подія створення кімнати
+    RoomJoin(RoomJoin), //Подія приєднання до кімнати
+    RoomLeave, //Подія виходу з кімнати
+    RoomReady, //Подія перемикання готовності
+    RoomEdit(RoomEdit), //Подія оновлення параметрів кімнати
+    RoomMessage(RoomMessage), //Подія надсилання повідомлення в чат кімнати
+    RoomEmote(game::rooms::chat::Emote), //Подія надсилання реакції в чат кімнати
+    GameStart(GameStart), //Подія створення нової гри
+    GamePlay(GamePlay), //Подія ходу у грі
 }
 
 impl Payload {
-    pub fn to_json_string(&self) -> String { //серіалізація у рядок
-        serde_json::to_string(self).expect("Failed to serialize Gateway Payload")
+    pub fn to_event(&self) -> Event { //серіалізація у подію для каналу з'єднання
+        serde_json::to_value(self).expect("Failed to serialize Gateway Payload")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Request { //Структура, що описує повідомлення клієнта
+    #[serde(default)]
+    pub nonce: Option<String>, //значення, яке повертається у відповіді на це повідомлення
+    #[serde(flatten)]
+    pub payload: Payload,
+}
+
+impl Request {
+    pub fn from_json_str(text: &str) -> Result<Self, serde_json::Error> { //десеріалізація з рядка
+        serde_json::from_str::<Self>(text).or_else(|e| {
+            //повідомлення без nonce у вигляді рядка, наприклад "QueueLeave", не є об'єктом, тому десеріалізуються напряму
+            serde_json::from_str::<Payload>(text).map(|payload| Self { nonce: None, payload }).map_err(|_| e)
+        })
+    }
+
+    pub fn from_msgpack_slice(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> { //десеріалізація з MessagePack
+        rmp_serde::from_slice::<Self>(bytes).or_else(|e| {
+            rmp_serde::from_slice::<Payload>(bytes).map(|payload| Self { nonce: None, payload }).map_err(|_| e)
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Reply { //Структура, що описує відповідь на повідомлення клієнта
+    #[serde(skip_serializing_if = "Option::is_none")] //без nonce відповідь не відрізняється від звичайного Payload
+    pub nonce: Option<String>,
+    #[serde(flatten)]
+    pub payload: Payload,
+}
+
+impl Reply {
+    pub fn to_event(&self) -> Event { //серіалізація у подію для каналу з'єднання
+        serde_json::to_value(self).expect("Failed to serialize Gateway Reply")
     }
 }
 
@@ -54,6 +141,22 @@ pub enum Error { //Список помилок
         InternalServerError,
         NotFound,
         Forbidden,
+        Unauthorized,
+        TooManyRequests,
+        BadArgument(String),
+        Conflict(String),
+}
+
+impl From<game::rooms::Error<'_>> for Error { //Перетворення помилок кімнат у помилки gateway
+    fn from(value: game::rooms::Error<'_>) -> Self {
+        match value {
+            game::rooms::Error::BadArgument(message) => Error::BadArgument(message.to_string()),
+            game::rooms::Error::Forbidden(_) | game::rooms::Error::SpectatingNotAllowed => Error::Forbidden,
+            game::rooms::Error::PlayerNotInRoom | game::rooms::Error::NoGame => Error::NotFound,
+            game::rooms::Error::RateLimited => Error::TooManyRequests,
+            other => Error::Conflict(format!("{:?}", other)), //наприклад Full або Game(CardNotInHand)
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -67,6 +170,93 @@ impl Hello {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Heartbeat { //Структура, що описує подію перевірки з'єднання
+    pub timestamp: i64, //час надсилання у мілісекундах за годинником відправника, повертається без змін
+}
+
+impl Heartbeat {
+    pub fn now() -> Self { //Конструктор з поточним часом
+        Self { timestamp: chrono::Utc::now().timestamp_millis() }
+    }
+
+    pub fn elapsed(&self) -> u64 { //Час, що минув з моменту надсилання, у мілісекундах
+        (chrono::Utc::now().timestamp_millis() - self.timestamp).max(0) as u64
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueueJoin { //Структура, що описує подію входу в чергу
+    players: usize, //бажана кількість гравців
+}
+
+impl QueueJoin {
+    pub fn players(&self) -> usize { //Геттер бажаної кількості гравців
+        self.players
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomSetup { //Структура, що описує подію створення кімнати, поля відповідають запиту POST /api/rooms
+    pub name: String,
+    pub is_public: bool,
+    pub password: Option<String>,
+    pub max_players: usize,
+    #[serde(default)]
+    pub rules: RuleSet,
+    #[serde(default)]
+    pub allow_spectators: bool,
+    #[serde(default)]
+    pub spectator_delay: u64,
+    #[serde(default)]
+    pub auto_start: u64,
+    #[serde(default)]
+    pub series: game::rooms::series::Target,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomJoin { //Структура, що описує подію приєднання до кімнати
+    pub room: String, //ідентифікатор кімнати
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomEdit { //Структура, що описує подію оновлення кімнати, поля відповідають запиту PATCH /api/rooms/:id
+    pub name: Option<String>,
+    pub is_public: Option<bool>,
+    pub password: Option<String>,
+    pub owner: Option<Uuid>,
+    pub max_players: Option<usize>,
+    pub rules: Option<RuleSet>,
+    pub allow_spectators: Option<bool>,
+    pub spectator_delay: Option<u64>,
+    pub auto_start: Option<u64>,
+    pub series: Option<game::rooms::series::Target>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomMessage { //Структура, що описує подію надсилання повідомлення в чат
+    pub content: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameStart { //Структура, що описує подію створення гри
+    pub seed: Option<u64>, //зерно генератора карт
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamePlay { //Структура, що описує подію ходу
+    pub card: Option<u32>, //ідентифікатор карти, None - взяти карту з колоди
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Resume { //Структура, що описує подію відновлення сесії
+    pub token: String,
+    pub last_seq: u64, //номер останньої отриманої клієнтом події
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Identify { //Структура, що описує подію ідентифікації
     token: String,
@@ -76,4 +266,59 @@ impl Identify {
     pub fn token(&self) -> String { //Геттер token
         self.token.clone()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast;
+    use crate::{ database::entities::accounts, gateway::sessions::{ Replay, User } };
+
+    fn reply(payload: Payload) -> Event { //Серіалізація відповіді з nonce
+        Reply { nonce: Some(String::from("1")), payload }.to_event()
+    }
+
+    fn assert_reply(payload: Payload, name: &str) { //Відповідь повинна містити nonce та назву події
+        let value = reply(payload);
+        assert_eq!(value["nonce"], "1");
+        assert!(value.get(name).is_some(), "{name} is missing in {value}");
+    }
+
+    #[test]
+    fn every_reply_variant_serializes() {
+        let (sender, _receiver) = broadcast::channel::<Event>(1);
+        let account = accounts::Model {
+            id: 1,
+            created_at: chrono::Utc::now().fixed_offset(),
+            uuid: Uuid::new_v4(),
+            login: String::from("login"),
+            password: String::new(),
+            display_name: String::from("login"),
+            games_played: 0,
+            points: 0,
+            cards_had: 0,
+            wins: 0,
+            loses: 0,
+            max_points: 0,
+            rating: 0.0,
+            rating_deviation: 0.0,
+            series_played: 0,
+            series_won: 0,
+        };
+        let status = serde_json::from_str(r#"{"queued":true,"players":2,"waited":0}"#).unwrap();
+        assert_reply(Payload::Ack {}, "Ack");
+        assert_reply(Payload::Resumed {}, "Resumed");
+        assert_reply(Payload::InvalidSession {}, "InvalidSession");
+        assert_reply(Payload::Error(Error::Declined), "Error");
+        assert_reply(Payload::HeartbeatAck(Heartbeat::now()), "HeartbeatAck");
+        assert_reply(Payload::QueueStatus(status), "QueueStatus");
+        assert_reply(Payload::LobbyRooms(Vec::new()), "LobbyRooms");
+        assert_reply(Payload::RoomCreate(game::rooms::Room::default()), "RoomCreate");
+        assert_reply(Payload::Ready(User::from_account(account, sender, Replay::new())), "Ready");
+    }
+
+    #[test]
+    fn reply_without_nonce_is_plain_payload() {
+        let value = Reply { nonce: None, payload: Payload::Ack {} }.to_event();
+        assert_eq!(value, serde_json::json!({ "Ack": {} }));
+    }
+}
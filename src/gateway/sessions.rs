@@ -3,28 +3,101 @@ use sea_orm::prelude::Uuid;
 use std::{
     hash::{Hash, Hasher},
     borrow::Borrow,
+    collections::VecDeque,
+    sync::{ Arc, Mutex },
 };
-use tokio::sync::broadcast::Sender;
-use crate::database::entities;
+use tokio::sync::{ broadcast::Sender, mpsc::UnboundedSender };
+use crate::{ database::entities, gateway::payloads::Event };
+
+const REPLAY_CAPACITY: usize = 256; //Кількість останніх подій, які можна повторити при відновленні сесії
+
+pub type ReplayBuffer = Arc<Mutex<Replay>>; //Посилання на буфер, спільне для сесії та потоку надсилання з'єднання
+
+#[derive(Debug, Clone)]
+pub struct Connection { //Структура, що описує канали одного gateway з'єднання
+    pub sender: Sender<Event>, //надсилач подій, які нумеруються
+    pub replay: ReplayBuffer, //буфер, в якому нумеруються події цього з'єднання
+    pub missed: UnboundedSender<Event>, //надсилач вже пронумерованих подій, які повторюються без змін
+}
+
+#[derive(Debug, Default)]
+pub struct Replay { //Структура, що описує нумерацію та буфер надісланих подій сесії
+    seq: u64, //номер останньої надісланої події
+    events: VecDeque<(u64, Event)>, //останні надіслані події з їхніми номерами
+    detached: bool, //буфер вже переданий новому з'єднанню і більше не поповнюється
+}
+
+impl Replay {
+    pub fn new() -> ReplayBuffer { //Конструктор порожнього буфера
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    pub fn push(&mut self, event: Event) -> Event { //Присвоєння події наступного номера та збереження її в буфері
+        self.seq += 1;
+        let event = match event {
+            Event::Object(mut object) => { //подія з даними, додати поле seq
+                object.insert(String::from("seq"), self.seq.into());
+                Event::Object(object)
+            },
+            Event::String(name) => { //подія без даних, наприклад "RoomCountdownCancel", перетворюється на об'єкт
+                serde_json::json!({ "seq": self.seq, name: null })
+            },
+            event => event,
+        };
+        if self.events.len() >= REPLAY_CAPACITY { self.events.pop_front(); }
+        self.events.push_back((self.seq, event.clone()));
+        event
+    }
+
+    pub fn detach(&mut self) -> Self { //Передача нумерації та подій новому з'єднанню
+        let replay = std::mem::take(self);
+        self.detached = true;
+        replay
+    }
+
+    pub fn is_detached(&self) -> bool { //Чи переданий буфер новому з'єднанню
+        self.detached
+    }
+
+    pub fn clear(&mut self) { //Очищення буфера, після якого відновлення до поточного номера неможливе
+        self.events.clear();
+    }
+
+    pub fn missed(&self, last_seq: u64) -> Option<Vec<Event>> { //Події після last_seq, None якщо буфер вже не містить усіх
+        if last_seq > self.seq { return None } //клієнт не міг отримати подію, яка ще не була надіслана
+        let first = self.events.front().map(|(seq, _)| *seq).unwrap_or(self.seq + 1);
+        if last_seq + 1 < first { return None } //частина пропущених подій вже витіснена з буфера
+        Some(self.events.iter().filter(|(seq, _)| *seq > last_seq).map(|(_, event)| event.clone()).collect())
+    }
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct User { //Структура, яка описує сесію користувача
     #[serde(skip)]
-    pub sender: Sender<String>, //надсилач
+    pub sender: Sender<Event>, //надсилач
+    #[serde(skip)]
+    pub replay: ReplayBuffer, //буфер надісланих подій для відновлення сесії
     uuid: Uuid, //id гравця
     pub room: Option<String>, //кімната до якої гравець приєднався
+    pub spectating: Option<String>, //кімната за якою гравець спостерігає
+    pub latency: Option<u64>, //затримка з'єднання у мілісекундах за останнім HeartbeatAck
+    pub lobby: bool, //чи підписаний гравець на лобі
 }
 
 impl User {
-    pub fn from_account(account: entities::accounts::Model, sender: Sender<String>) -> Self { //конструктор з рядку БД та надсилача
+    pub fn from_account(account: entities::accounts::Model, sender: Sender<Event>, replay: ReplayBuffer) -> Self { //конструктор з рядку БД, надсилача та буфера подій
         Self { 
             sender,
+            replay,
             uuid: account.uuid,
             room: None,
+            spectating: None,
+            latency: None,
+            lobby: false,
         }
     }
 
-    pub fn set_sender(&mut self, sender: Sender<String>) { //Геттер надсилача
+    pub fn set_sender(&mut self, sender: Sender<Event>) { //Геттер надсилача
         self.sender = sender;
     }
 
@@ -51,4 +124,60 @@ impl Borrow<Uuid> for User { //Реалізація повернення пол
     fn borrow(&self) -> &Uuid {
         &self.uuid
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(events: usize) -> Replay { //Буфер з наданою кількістю надісланих подій
+        let mut replay = Replay::default();
+        for _ in 0..events {
+            replay.push(serde_json::json!({ "Ack": {} }));
+        }
+        replay
+    }
+
+    fn seqs(events: Vec<Event>) -> Vec<u64> {
+        events.iter().map(|event| event["seq"].as_u64().unwrap()).collect()
+    }
+
+    #[test]
+    fn push_numbers_events() {
+        let mut replay = Replay::default();
+        assert_eq!(replay.push(serde_json::json!({ "Ack": {} })), serde_json::json!({ "Ack": {}, "seq": 1 }));
+        assert_eq!(replay.push(serde_json::json!("RoomCountdownCancel")), serde_json::json!({ "RoomCountdownCancel": null, "seq": 2 }));
+    }
+
+    #[test]
+    fn missed_returns_events_after_last_seq() {
+        let replay = replay(5);
+        assert_eq!(replay.missed(2).map(seqs), Some(vec![3, 4, 5]));
+        assert_eq!(replay.missed(0).map(seqs), Some(vec![1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn missed_is_empty_for_current_seq() {
+        assert_eq!(replay(5).missed(5).map(seqs), Some(Vec::new()));
+    }
+
+    #[test]
+    fn missed_rejects_seq_ahead_of_buffer() {
+        assert!(replay(5).missed(6).is_none()); //клієнт не міг отримати подію, яка ще не була надіслана
+    }
+
+    #[test]
+    fn missed_rejects_seq_older_than_buffer() {
+        let replay = replay(REPLAY_CAPACITY + 10); //перші 10 подій витіснені з буфера
+        assert!(replay.missed(9).is_none());
+        assert_eq!(replay.missed(10).map(|events| events.len()), Some(REPLAY_CAPACITY));
+    }
+
+    #[test]
+    fn missed_rejects_cleared_buffer() {
+        let mut replay = replay(5);
+        replay.clear();
+        assert!(replay.missed(4).is_none());
+        assert_eq!(replay.missed(5).map(seqs), Some(Vec::new())); //клієнт, що отримав усе, все ще може відновитися
+    }
+}
@@ -0,0 +1,75 @@
+use poem::{
+    handler, http::StatusCode, web::{
+        Data, Json, Path
+    },
+};
+use sea_orm::{prelude::{ DateTimeWithTimeZone, Uuid }, DatabaseConnection};
+use std::{ops::Deref, sync::Arc};
+use crate::database::{ entities::{ match_moves, match_players, matches }, queries };
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct Participant { //Структура, що описує учасника гри
+    player: Uuid, //ідентифікатор гравця
+    place: i16, //місце, 0 - вибув першим
+    points: i64, //очки за гру
+    cards_had: i32, //карт мав за гру загалом
+}
+
+impl From<match_players::Model> for Participant {
+    fn from(value: match_players::Model) -> Self {
+        Self { player: value.player, place: value.place, points: value.points, cards_had: value.cards_had }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Move { //Структура, що описує хід гри
+    number: i32, //порядковий номер ходу
+    player: Uuid, //гравець, що ходив
+    card: Option<serde_json::Value>, //зіграна карта, null - карта взята з колоди
+    effect: Option<serde_json::Value>, //ефект зіграної карти
+    dropped: bool, //гравця видалено з гри замість ходу
+}
+
+impl From<match_moves::Model> for Move {
+    fn from(value: match_moves::Model) -> Self {
+        Self { number: value.number, player: value.player, card: value.card, effect: value.effect, dropped: value.dropped }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Match { //Структура, що описує збережену гру
+    id: i64, //ідентифікатор
+    created_at: DateTimeWithTimeZone, //час завершення
+    room: String, //кімната, в якій відбувалася гра
+    seed: u64, //зерно генератора карт
+    rules: serde_json::Value, //правила гри
+    players: Vec<Participant>, //учасники у порядку вибування
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moves: Option<Vec<Move>>, //журнал ходів, надається тільки при запиті однієї гри
+}
+
+impl Match {
+    pub fn new(model: matches::Model, players: Vec<match_players::Model>, moves: Option<Vec<match_moves::Model>>) -> Self { //Конструктор з рядків БД
+        let mut players: Vec<Participant> = players.into_iter().map(Participant::from).collect();
+        players.sort_by_key(|player| player.place);
+        Self {
+            id: model.id,
+            created_at: model.created_at,
+            room: model.room,
+            seed: model.seed as u64, //зворотне перетворення зерна з bigint
+            rules: model.rules,
+            players,
+            moves: moves.map(|moves| moves.into_iter().map(Move::from).collect()),
+        }
+    }
+}
+
+#[handler]
+pub async fn get(Path(id): Path<i64>, db: Data<&Arc<DatabaseConnection>>) -> Result<Json<Match>, StatusCode> { //функція доставання гри з журналом ходів
+    let db = db.deref().as_ref();
+    let (model, players, moves) = queries::matches::by_id(db, id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(Match::new(model, players, Some(moves))))
+}
@@ -0,0 +1,48 @@
+use poem::{handler, http::StatusCode, web::{ Data, Json }, Request };
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use std::{ ops::Deref, sync::Arc };
+use crate::{ Rooms, database::queries, game::matchmaking::QueueStatus };
+use super::rooms::prelude;
+
+#[derive(Deserialize)]
+struct QueueJoin { //структура, яка описує поля запиту на вхід в чергу
+    players: usize, //бажана кількість гравців
+}
+
+#[handler]
+pub async fn join( //функція входу в чергу пошуку гри
+    req: &Request,
+    body: Json<QueueJoin>,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+) -> Result<Json<QueueStatus>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (_players, _rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    if player.room.is_some() || player.spectating.is_some() { return Err(StatusCode::FORBIDDEN) } //гравець вже в кімнаті
+    let account = queries::accounts::by_uuid(*player.uuid()).one(db).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?; //рейтинг гравця для підбору
+    let status = queue_ptr.write().await.join(*player.uuid(), player.sender.clone(), account.rating, body.players)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    Ok(Json(status))
+}
+
+#[handler]
+pub async fn leave( //функція виходу з черги пошуку гри
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+) -> Result<Json<QueueStatus>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (_players, _rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let status = queue_ptr.write().await.leave(player.uuid()).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(status))
+}
@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod matches;
+pub mod matchmaking;
 pub mod rooms;
 pub mod users;
\ No newline at end of file
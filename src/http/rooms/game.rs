@@ -1,13 +1,12 @@
-use poem::{handler, http::StatusCode, web::{ Data, Path }, Request, Response };
-use sea_orm::{prelude::DatabaseConnection, Set};
+use poem::{handler, http::StatusCode, web::{ Data, Json, Path }, Request, Response };
+use sea_orm::prelude::DatabaseConnection;
 use serde::Deserialize;
 use tokio::sync::RwLock;
 use std::{ ops::Deref, sync::Arc };
 use crate::{ 
     Rooms,
     runtime_storage::Table,
-    game::gameplay::Ok,
-    database::queries::accounts,
+    game::{ gameplay::{ self, Ok }, rooms },
 };
 use super::prelude;
 use futures::executor;
@@ -21,39 +20,50 @@ pub async fn get( //функція доставання стану гри кім
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<Response, StatusCode> {
     let db = db.deref().as_ref();
-    let (_players, rooms, _player) =
+    let (_players, rooms, player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?; //виклик функції бронювання даних за показниками
-    //пошук кімнати в таблиці кімнат, обробка помилок та надсилання інформації про гру якщо існує
-    let game = rooms.get(&id).ok_or(StatusCode::NOT_FOUND)?.game.as_ref().ok_or(StatusCode::NO_CONTENT)?.read().await.clone(); 
+    let room = rooms.get(&id).ok_or(StatusCode::NOT_FOUND)?; //пошук кімнати в таблиці кімнат
+    //стан гри без затримки бачать тільки гравці кімнати, глядачі отримують його через gateway
+    if room.players().read().await.get(player.uuid()).is_none() { return Err(StatusCode::FORBIDDEN) }
+    //обробка помилок та надсилання інформації про гру якщо існує
+    let game = room.game.as_ref().ok_or(StatusCode::NO_CONTENT)?.read().await.clone(); 
     Ok(Response::builder().body(serde_json::to_string(&game).unwrap()))
 }
 
+#[derive(Deserialize)]
+struct GameStart { //структура, яка описує необов'язкове тіло запиту створення гри
+    seed: Option<u64>, //зерно генератора карт, задається власником кімнати
+}
+
 #[handler]
 pub async fn start( //функція для створення нової гри у кімнаті  
     Path(id): Path<String>,
     req: &Request,
+    body: Option<Json<GameStart>>, //тіло запиту не обов'язкове
     db: Data<&Arc<DatabaseConnection>>,
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<StatusCode, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для таймера ходу
     let db = db.deref().as_ref();
     let (_players, mut rooms, player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
     rooms.update(&id, |room| {
         if player.uuid() == room.owner() {
-            executor::block_on(room.start_game()).map_err(|_| StatusCode::CONFLICT) //блокування потоку доки не виконається асинхронний метод створення нової гри
+            executor::block_on(room.start_game(body.as_ref().and_then(|body| body.seed))).map_err(|_| StatusCode::CONFLICT) //блокування потоку доки не виконається асинхронний метод створення нової гри
         } else {
             Err(StatusCode::FORBIDDEN)
         }?;
         Ok::<(), StatusCode>(())
     })?;
+    if let Some(room) = rooms.get(&id) { room.watch_turn(db_ptr) } //запуск таймера першого ходу
     Ok(StatusCode::OK)
 }
 
 #[derive(Deserialize)]
 struct Play { //структура, яка описує необхідні значення в шляху
     id: String, //ідентифікатор кімнати
-    card_id: Option<usize>, //індекс карти у векторі карт гравця
+    card_id: Option<u32>, //ідентифікатор карти в руці гравця
 }
 
 #[handler]
@@ -64,27 +74,18 @@ pub async fn play(
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<StatusCode, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для таймера ходу
     let db = db.deref().as_ref();
     let (_players, mut rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
     let room = rooms.get(&id).ok_or(StatusCode::NOT_FOUND)?;
-    match room.play_game(*player.uuid(), card_id).await.map_err(|_e| { StatusCode::PRECONDITION_FAILED } )? { //виклик методу кімнати для ходіння у грі та обробка результату
-        Ok::GameOver(players) => { //якщо гра завершилася
-            for (index, player) in players.iter().enumerate() { //ітерація через усих гравців гри
-                let _ = accounts::update(db, player.id().clone(), |values, account| { //пошук їх акаунтів в БД та оновленя значень у замиканні
-                    account.games_played = Set(values.games_played + 1); //збільшення кількості зіграних ігор на 1
-                    if index < players.len() / 2 { //вважати виграшом якщо вибув з гри раніше за половину гравців
-                        account.wins = Set(values.wins + 1); //збільшення кількості виграшів    
-                    } else {
-                        account.loses = Set(values.loses + 1); //збільшення кількості програшів
-                    }
-                    account.cards_had = Set(values.cards_had + *player.cards_had() as i64); //збільшення кількості карт які гравець тримав загалом
-                    account.points = Set(values.points + *player.points() as i64); //збільшення кількості очків
-                    account.max_points = Set(values.max_points.max(*player.points() as i16)) //порівняння значення найбільшої кількості очок за гру та їх назначення
-                }).await;
-            }
-        },
-        _ => {},
+    let result = room.play_game(*player.uuid(), card_id).await.map_err(|e| match e {
+        rooms::Error::Game(gameplay::Error::CardNotInHand) => StatusCode::NOT_FOUND, //карти з таким ідентифікатором немає в руці гравця
+        _ => StatusCode::PRECONDITION_FAILED,
+    })?; //виклик методу кімнати для ходіння у грі та обробка результату
+    match result {
+        Ok::GameOver(players) => room.save_results(db, &players).await, //якщо гра завершилася, то зберегти статистику акаунтів та історію гри
+        _ => room.watch_turn(db_ptr), //інакше запустити таймер нового ходу
     }
     Ok(StatusCode::OK)
 }
\ No newline at end of file
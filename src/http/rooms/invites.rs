@@ -0,0 +1,105 @@
+use poem::{handler, http::StatusCode, web::{ Data, Json, Path }, Request };
+use sea_orm::prelude::DatabaseConnection;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use std::{ ops::Deref, sync::Arc };
+use crate::{
+    Rooms,
+    game::rooms::{ invite::Invite, Room },
+};
+use super::prelude;
+
+fn expires_in() -> u64 { 24 * 60 * 60 } //функція для задання значення за замовченням при десеріалізації
+
+#[derive(Deserialize)]
+struct InviteCreate { //структура, яка описує поля запиту створення запрошення
+    #[serde(default = "expires_in")]
+    expires_in: u64, //час дії у секундах, за замовченням доба
+    max_uses: Option<u32>, //кількість використань, поле не обов'язкове
+}
+
+#[derive(Deserialize)]
+struct InvitePath { //структура, яка описує необхідні значення в шляху
+    id: String, //ідентифікатор кімнати
+    code: String, //код запрошення
+}
+
+#[handler]
+pub async fn list( //функція доставання дійсних запрошень кімнати
+    Path(id): Path<String>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<Json<Vec<Invite>>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (_players, mut rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let mut room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone(); //пошук кімнати
+    if *room.owner() != *player.uuid() { return Err(StatusCode::FORBIDDEN) } //запрошення бачить тільки власник
+    let invites = room.invites();
+    rooms.0.replace(room); //збереження без оголошення, запрошення не є частиною кімнати для гравців
+    Ok(Json(invites))
+}
+
+#[handler]
+pub async fn create( //функція створення запрошення
+    Path(id): Path<String>,
+    req: &Request,
+    body: Json<InviteCreate>,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<Json<Invite>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (_players, mut rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let mut room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone();
+    if *room.owner() != *player.uuid() { return Err(StatusCode::FORBIDDEN) } //тільки власник може запрошувати
+    let invite = room.create_invite(*player.uuid(), body.expires_in, body.max_uses).map_err(|_| StatusCode::BAD_REQUEST)?;
+    rooms.0.replace(room);
+    Ok(Json(invite))
+}
+
+#[handler]
+pub async fn revoke( //функція відкликання запрошення
+    Path(InvitePath { id, code }): Path<InvitePath>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<StatusCode, StatusCode> {
+    let db = db.deref().as_ref();
+    let (_players, mut rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let mut room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone();
+    if *room.owner() != *player.uuid() { return Err(StatusCode::FORBIDDEN) }
+    room.revoke_invite(&code).map_err(|_| StatusCode::NOT_FOUND)?;
+    rooms.0.replace(room);
+    Ok(StatusCode::OK)
+}
+
+#[handler]
+pub async fn redeem( //функція приєднання до кімнати за кодом запрошення
+    Path(code): Path<String>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+) -> Result<Json<Room>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (mut players, mut rooms, mut player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let mut room = rooms.iter().find(|room| room.has_invite(&code)).ok_or(StatusCode::NOT_FOUND)?.clone(); //пошук кімнати, якій належить код
+    if player.spectating.is_some() { return Err(StatusCode::FORBIDDEN) } //глядач повинен припинити спостереження перед приєднанням
+    if let Some(room_id) = &player.room { //гравець вже в кімнаті
+        return if room_id == room.id() { Ok(Json(room)) } else { Err(StatusCode::FORBIDDEN) }
+    }
+    room.join_by_invite(&code, *player.uuid(), player.sender.clone()).await.map_err(|_| StatusCode::FORBIDDEN)?;
+    rooms.0.replace(room.clone()); //збереження використаного запрошення
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
+    player.room = Some(room.id().clone()); //оновити значення кімнати в сесії
+    players.replace(player);
+    Ok(Json(room))
+}
@@ -1,4 +1,5 @@
 pub mod game;
+pub mod invites;
 
 use poem::{handler, http::StatusCode, web::{ self, Data, Json, Path }, Request, Response };
 use sea_orm::{ prelude::Uuid, DatabaseConnection };
@@ -7,13 +8,13 @@ use tokio::sync::{ RwLock, RwLockWriteGuard};
 use std::{ ops::Deref, sync::Arc };
 use crate::{ 
     Rooms,
-    game::rooms::{self, Room, Partial},
+    game::{ rooms::{self, Room, Partial, series::Target}, gameplay::{ bot::Bot, rules::RuleSet, Ok } },
     database::queries,
     gateway::sessions::User,
     runtime_storage::Table,
 };
 
-async fn prelude<'a>( //функція для валідації токена, пошук акаунта за токеном та бронювання показників 
+pub(crate) async fn prelude<'a>( //функція для валідації токена, пошук акаунта за токеном та бронювання показників 
     db: &'a DatabaseConnection,
     auth: Option<&'a str>,
     players_ptr: &'a Arc<RwLock<crate::Players>>,
@@ -36,25 +37,121 @@ async fn prelude<'a>( //функція для валідації токена, 
 
 fn limit() -> usize { 100 } //функція для задання значення за замовченням при десеріалізації
 
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum RoomSort { //список ключів сортування списку кімнат
+    #[default]
+    CreatedAt, //час створення
+    Players, //кількість гравців
+    Name, //назва без урахування регістру
+}
+
 #[derive(Deserialize)]
 struct RoomQuery { //структура, яка описує необхідні Query параметри у шляху, необхідна для пажировки списку кімнат
-    #[serde(default)] //використання значення usize за замовченням - 0
-    after: usize, //поле, яке указує після якої кількості кімнат необхідно почати формувати список для відповіді
+    name: Option<String>, //частина назви, без урахування регістру
+    has_password: Option<bool>, //чи має кімната пароль
+    free_seats: Option<usize>, //мінімальна кількість вільних місць
+    in_game: Option<bool>, //чи йде в кімнаті гра
+    max_players_from: Option<usize>, //межі максимальної кількості гравців
+    max_players_to: Option<usize>,
+    #[serde(default)]
+    sort: RoomSort, //ключ сортування
+    #[serde(default)]
+    desc: bool, //сортування за спаданням
+    after: Option<String>, //курсор, отриманий у полі next попередньої сторінки
     #[serde(default = "limit")] //виклик функції, якщо значення не отримано
     limit: usize, //поле, яке указує максимальну кількість кімнат які список для відповіді має містити
 }
 
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey { //значення ключа сортування кімнати
+    Number(i64),
+    Text(String),
+}
+
+impl SortKey {
+    fn new(sort: RoomSort, room: &Room, players: usize) -> Self {
+        match sort {
+            RoomSort::CreatedAt => SortKey::Number(*room.created_at()),
+            RoomSort::Players => SortKey::Number(players as i64),
+            RoomSort::Name => SortKey::Text(room.name().to_lowercase()),
+        }
+    }
+
+    fn parse(sort: RoomSort, value: &str) -> Option<Self> { //відновлення ключа з курсора
+        match sort {
+            RoomSort::Name => Some(SortKey::Text(value.to_string())),
+            _ => value.parse().ok().map(SortKey::Number),
+        }
+    }
+
+    fn cursor(&self, id: &str) -> String { //курсор складається з ідентифікатора кімнати та значення ключа, ідентифікатор не містить крапок
+        let value = match self {
+            SortKey::Number(value) => value.to_string(),
+            SortKey::Text(value) => value.clone(),
+        };
+        format!("{}.{}", id, value)
+    }
+}
+
+fn parse_cursor(sort: RoomSort, cursor: &str) -> Option<(SortKey, String)> { //відновлення ключа та ідентифікатора з курсора
+    let (id, value) = cursor.split_once('.')?;
+    Some((SortKey::parse(sort, value)?, id.to_string()))
+}
+
+//Сторінка значень після курсора, повертає також курсор наступної сторінки
+//ідентифікатор доповнює ключ, щоб порядок був однозначним навіть для однакових значень
+fn paginate<T>(mut items: Vec<(SortKey, String, T)>, after: Option<&(SortKey, String)>, desc: bool, limit: usize) -> (Vec<T>, Option<String>) {
+    items.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    if desc { items.reverse() }
+    let mut page: Vec<(SortKey, String, T)> = items.into_iter()
+        .filter(|(key, id, _)| match after { //пропуск значень до курсора включно
+            Some(after) if desc => (key, id) < (&after.0, &after.1),
+            Some(after) => (key, id) > (&after.0, &after.1),
+            None => true,
+        })
+        .take(limit + 1) //зайве значення показує, що є наступна сторінка
+        .collect();
+    let next = if page.len() > limit { page.get(limit - 1).map(|(key, id, _)| key.cursor(id)) } else { None };
+    page.truncate(limit);
+    (page.into_iter().map(|(_, _, item)| item).collect(), next)
+}
+
+#[derive(Serialize)]
+struct RoomList { //структура відповіді зі сторінкою кімнат
+    rooms: Vec<Partial>,
+    next: Option<String>, //курсор наступної сторінки, None - сторінка остання
+}
+
 #[handler]
-pub async fn get_rooms_list(query: web::Query<RoomQuery>, rooms: Data<&Arc<RwLock<Rooms>>>) -> Json<Vec<Partial>> { //функція для формування списку
+pub async fn get_rooms_list(query: web::Query<RoomQuery>, rooms: Data<&Arc<RwLock<Rooms>>>) -> Result<Json<RoomList>, StatusCode> { //функція для формування списку
+    let after = match &query.after { //курсор, після якого починається сторінка
+        Some(cursor) => Some(parse_cursor(query.sort, cursor).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    let limit = query.limit.clamp(1, 100);
+    let name = query.name.as_ref().map(|name| name.to_lowercase());
     let rooms = rooms.read().await; //замок на таблицю кімнат
-    let mut rooms_vec: Vec<Partial> = rooms //таблиця кімнат
-        .iter() //її метод для ітерації
-        .filter(|room| room.is_public) //фільтр значеня за полем is_public
-        .skip(query.after) //пропуск перших query.after значень в rooms
-        .take(query.limit) //беремо query.limit значень
-        .map(|room| Partial(room.clone().into())) //перетворюємо в часткову модель
-        .collect(); //та збираємо у новий вектор
-    Json(rooms_vec) //надсилання відповіді
+    let mut rooms_vec = Vec::new();
+    for room in rooms.iter().filter(|room| room.is_public) { //фільтр значень за полем is_public
+        let players = room.players().read().await.len();
+        if name.as_ref().is_some_and(|name| !room.name().to_lowercase().contains(name)) { continue }
+        if query.has_password.is_some_and(|value| value != room.has_password()) { continue }
+        if query.free_seats.is_some_and(|value| room.max_players().saturating_sub(players) < value) { continue }
+        let in_game = match room.game() { //гра розпочата і ще не закінчена
+            Some(game) => !*game.read().await.gameover(),
+            None => false,
+        };
+        if query.in_game.is_some_and(|value| value != in_game) { continue }
+        if query.max_players_from.is_some_and(|value| *room.max_players() < value) { continue }
+        if query.max_players_to.is_some_and(|value| *room.max_players() > value) { continue }
+        rooms_vec.push((SortKey::new(query.sort, room, players), room.id().clone(), room));
+    }
+    let (page, next) = paginate(rooms_vec, after.as_ref(), query.desc, limit);
+    Ok(Json(RoomList {
+        rooms: page.into_iter().map(|room| Partial(room.clone())).collect(), //перетворюємо в часткову модель
+        next,
+    }))
 }
 
 #[derive(Deserialize)]
@@ -63,6 +160,16 @@ struct RoomCreate { //структура, яка задає необхідні 
     is_public: bool, //публічність
     password: Option<String>, //пароль, поле не обов'язкове
     max_players: usize, //максимальна кількість гравців
+    #[serde(default)] //правила за замовченням, якщо поле не надане
+    rules: RuleSet, //правила гри
+    #[serde(default)]
+    allow_spectators: bool, //чи дозволено спостерігати за кімнатою
+    #[serde(default)]
+    spectator_delay: u64, //затримка подій гри для глядачів у секундах
+    #[serde(default)]
+    auto_start: u64, //тривалість відліку до автоматичного початку гри у секундах, 0 - вимкнено
+    #[serde(default)]
+    series: Target, //умова завершення серії ігор
 }
 
 #[handler]
@@ -72,20 +179,28 @@ pub async fn create(
     db: Data<&Arc<DatabaseConnection>>,
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+    lobby_ptr: Data<&Arc<RwLock<crate::Lobby>>>,
 ) -> Result<Response, StatusCode> {
     let db = db.deref().as_ref();
     let (mut players, mut rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?; //виклик функції валідації токена та бронювання показників
-    if let Some(_) = &player.room { //якщо гравець вже в кімнаті то повернути помилку
+    if player.room.is_some() || player.spectating.is_some() { //якщо гравець вже в кімнаті або спостерігає то повернути помилку
         return Err(StatusCode::FORBIDDEN);
     }
 
-    let mut room = Room::create(body.name.clone(), body.is_public, body.password.clone(), *player.uuid(), body.max_players, player.sender.clone()).await
+    let mut room = Room::create(body.name.clone(), body.is_public, body.password.clone(), *player.uuid(), body.max_players, body.rules.clone(), player.sender.clone()).await
         .map_err(|_| StatusCode::BAD_REQUEST)?; //створити кімнату та обробити помилки
+    room.set_lobby(lobby_ptr.read().await.feed()); //публічна кімната з'являється у списку підписників лобі
+    room.allow_spectators = body.allow_spectators;
+    room.set_spectator_delay(body.spectator_delay).map_err(|_| StatusCode::BAD_REQUEST)?;
+    room.set_auto_start(body.auto_start).map_err(|_| StatusCode::BAD_REQUEST)?;
+    room.set_series(body.series).await.map_err(|_| StatusCode::BAD_REQUEST)?;
     while let Some(_) = rooms.get(&room.clone()) { //доки в таблиці вже існує кімната з таким ідентифікатором
         room.regenerate_id() //згенерувати новий
     };
     rooms.insert(room.clone()); //вставити кімнату в таблицю
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
     player.room = Some(room.id().clone()); //задання кімнати для відновлення сесії
     players.replace(player); //оновлення користувача в таблиці користувачів
     Ok(Response::builder() //відповідь
@@ -100,6 +215,11 @@ struct RoomUpdate { //структура, яка описує необхідні
     password: Option<String>,
     owner: Option<Uuid>,
     max_players: Option<usize>,
+    rules: Option<RuleSet>,
+    allow_spectators: Option<bool>,
+    spectator_delay: Option<u64>,
+    auto_start: Option<u64>,
+    series: Option<Target>,
 }
 
 
@@ -112,6 +232,7 @@ pub async fn update( //функція для оновлення параметр
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<Response, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для відліку до автоматичного початку гри
     let db = db.deref().as_ref();
     let (_players, mut rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
@@ -126,6 +247,10 @@ pub async fn update( //функція для оновлення параметр
     if let Some(ref value) = body.password { result.push(room.set_password(Some(value.to_string()))) }
     if let Some(value) = body.owner { result.push(room.set_owner(value)) }
     if let Some(value) = body.max_players { result.push(room.set_max_players(value)) }
+    if let Some(ref value) = body.rules { result.push(room.set_rules(value.clone())) }
+    if let Some(value) = body.allow_spectators { room.allow_spectators = value; result.push(Ok(())) }
+    if let Some(value) = body.spectator_delay { result.push(room.set_spectator_delay(value)) }
+    if let Some(value) = body.auto_start { result.push(room.set_auto_start(value)) }
     for i in &result { 
             if let Err(_) = i { return Ok( //якщо вектор має хоч одну помилку, повернути відповідь з усима помилками не змінюючи кімнату
                 Response::builder().status(StatusCode::BAD_REQUEST).body(
@@ -134,7 +259,13 @@ pub async fn update( //функція для оновлення параметр
             )
         }
     };
-    rooms.replace(room); //інакше оновити кімнату
+    if let Some(value) = body.series { //серія змінюється одразу для усіх копій кімнати, тому тільки після перевірки інших полів
+        if let Err(e) = room.set_series(value).await {
+            return Ok(Response::builder().status(StatusCode::BAD_REQUEST).body(serde_json::to_string(&[Err::<(), _>(e)]).unwrap()))
+        }
+    }
+    rooms.replace(room.clone()); //інакше оновити кімнату
+    room.start_countdown(db_ptr, Arc::clone(&rooms_ptr)).await; //відлік міг бути щойно увімкнений, коли усі вже готові
     Ok(Response::builder().status(StatusCode::OK).body("")) //та повернути відповідь з статусом 200 успіх
 
 }
@@ -152,11 +283,13 @@ pub async fn join( //функція для приєднання до кімна
     db: Data<&Arc<DatabaseConnection>>,
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
 ) -> Result<Json<Room>, StatusCode> {
     let db = db.deref().as_ref();
     let (mut players, rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
     let room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone(); //пошук кімнати в таблиці
+    if player.spectating.is_some() { return Err(StatusCode::FORBIDDEN) } //глядач повинен припинити спостереження перед приєднанням
     if let Some(room_id) = &player.room { //сесія гравця вже містить якусь кімнату
         if id != *room_id { //і якщо ця кімната не є кімнатою до якої гравець хоче доєднатися
             return Err(StatusCode::FORBIDDEN); //повернути помилку
@@ -165,6 +298,7 @@ pub async fn join( //функція для приєднання до кімна
         }
     }
     room.join(body.password.clone(), *player.uuid(), player.sender.clone()).await.map_err(|_| StatusCode::FORBIDDEN)?; //якщо гравець не доєднаний до кімнати, доєднатися
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
     player.room = Some(room.id().clone()); //оновити значення кімнати в сесії
     players.replace(player); //оновити сесію в таблиці
     Ok(Json(room))  //відповідь
@@ -179,6 +313,7 @@ pub async fn leave( //функція для виходу з кімнати
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<StatusCode, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для відліку до автоматичного початку гри
     let db = db.deref().as_ref();
     let (mut players, mut rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
@@ -187,9 +322,11 @@ pub async fn leave( //функція для виходу з кімнати
     if let Err(rooms::Error::CantAssignNewOwner) = leave { //якщо не залишилося гравців
         rooms.remove(&room.clone()); //то видалити кімнату з таблиці
     } else if let Ok(true) = leave { //інакше якщо гравці є і власник змінився
-        rooms.replace(room); //то оновити кімнату в таблиці
+        rooms.replace(room.clone()); //то оновити кімнату в таблиці
+        room.start_countdown(db_ptr, Arc::clone(&rooms_ptr)).await; //решта гравців може бути готовою
     } else {
         leave.map_err(|_| StatusCode::FORBIDDEN)?; //інакше видати помилку
+        room.start_countdown(db_ptr, Arc::clone(&rooms_ptr)).await;
     }
     player.room = None; //очистити поле кімнати з сесії
     players.replace(player); //оновити сесію в таблиці
@@ -204,10 +341,185 @@ pub async fn ready( //функція для перемикання готовн
     players_ptr: Data<&Arc<RwLock<crate::Players>>>,
     rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
 ) -> Result<StatusCode, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для відліку до автоматичного початку гри
     let db = db.deref().as_ref();
     let (_players, mut rooms, mut player) =
         prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
     let room = rooms.get(&id).ok_or(StatusCode::NOT_FOUND)?; //пошу кімнати
     room.player_switch_ready(player.uuid().clone()).await.map_err(|_| StatusCode::FORBIDDEN)?; //виклик методу перемикання та обробка помилки
+    room.start_countdown(db_ptr, Arc::clone(&rooms_ptr)).await; //запуск відліку, якщо усі гравці готові
     Ok(StatusCode::OK) //відповідь
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize)]
+struct BotAdd { //структура, яка описує поля запиту на додавання бота
+    strategy: Bot, //стратегія бота
+}
+
+#[handler]
+pub async fn add_bot( //функція для додавання бота до кімнати
+    Path(id): Path<String>,
+    req: &Request,
+    body: Json<BotAdd>,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<Response, StatusCode> {
+    let db_ptr = Arc::clone(&db); //показник на з'єднання БД для відліку до автоматичного початку гри
+    let db = db.deref().as_ref();
+    let (_players, rooms, player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let room = rooms.get(&id).ok_or(StatusCode::NOT_FOUND)?; //пошук кімнати
+    if *room.owner() != *player.uuid() { return Err(StatusCode::FORBIDDEN) } //тільки власник може додавати ботів
+    let bot_id = room.add_bot(body.strategy).await.map_err(|_| StatusCode::CONFLICT)?; //додати бота, помилка якщо кімната заповнена
+    room.start_countdown(db_ptr, Arc::clone(&rooms_ptr)).await; //бот готовий одразу, тому відлік може початися
+    Ok(Response::builder().body(bot_id.to_string())) //відповідь з ідентифікатором бота
+}
+
+#[handler]
+pub async fn spectate( //функція для спостереження за кімнатою
+    Path(id): Path<String>,
+    req: &Request,
+    body: Json<RoomJoin>,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+    queue_ptr: Data<&Arc<RwLock<crate::Queue>>>,
+) -> Result<Json<Room>, StatusCode> {
+    let db = db.deref().as_ref();
+    let (mut players, mut rooms, mut player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    let room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone(); //пошук кімнати в таблиці
+    if player.room.is_some() || player.spectating.as_ref().is_some_and(|room_id| *room_id != id) { //гравець вже в іншій кімнаті
+        return Err(StatusCode::FORBIDDEN);
+    }
+    room.spectate(body.password.clone(), *player.uuid(), player.sender.clone()).await.map_err(|_| StatusCode::FORBIDDEN)?;
+    queue_ptr.write().await.cancel(player.uuid()); //гравець, що увійшов у кімнату вручну, більше не шукає гру
+    player.spectating = Some(room.id().clone()); //оновити значення кімнати спостереження в сесії
+    players.replace(player); //оновити сесію в таблиці
+    rooms.replace(room.clone()); //оголосити оновлення кімнати
+    Ok(Json(room))
+}
+
+#[handler]
+pub async fn stop_spectating( //функція для припинення спостереження за кімнатою
+    Path(id): Path<String>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<StatusCode, StatusCode> {
+    let db = db.deref().as_ref();
+    let (mut players, mut rooms, mut player) =
+        prelude(db, req.header("authorization"), players_ptr.deref(), rooms_ptr.deref()).await?;
+    if let Some(room) = rooms.get::<String>(&id).cloned() { //пошук кімнати
+        room.stop_spectating(*player.uuid()).await.map_err(|_| StatusCode::FORBIDDEN)?; //видалення глядача
+        rooms.replace(room); //оголосити оновлення кімнати
+    } else if player.spectating.as_ref() != Some(&id) { //якщо кімнату вже видалено, то тільки очистити сесію
+        return Err(StatusCode::NOT_FOUND);
+    }
+    player.spectating = None; //очистити поле кімнати спостереження з сесії
+    players.replace(player);
+    Ok(StatusCode::OK)
+}
+#[derive(Deserialize)]
+struct Kick { //структура, яка описує необхідні значення в шляху
+    id: String, //ідентифікатор кімнати
+    user: Uuid, //ідентифікатор гравця
+}
+
+async fn remove_player( //спільна логіка вигнання та блокування гравця
+    Kick { id, user }: Kick,
+    banned: bool, //true - також заборонити повторне приєднання
+    req: &Request,
+    db: &Arc<DatabaseConnection>,
+    players_ptr: &Arc<RwLock<crate::Players>>,
+    rooms_ptr: &Arc<RwLock<Rooms>>,
+) -> Result<StatusCode, StatusCode> {
+    let (mut players, mut rooms, player) =
+        prelude(db.as_ref(), req.header("authorization"), players_ptr, rooms_ptr).await?;
+    let mut room = rooms.get::<String>(&id).ok_or(StatusCode::NOT_FOUND)?.clone(); //пошук кімнати
+    if *room.owner() != *player.uuid() { return Err(StatusCode::FORBIDDEN) } //тільки власник може виганяти гравців
+    let result = room.kick(user, banned).await.map_err(|e| match e {
+        rooms::Error::PlayerNotInRoom => StatusCode::NOT_FOUND,
+        _ => StatusCode::FORBIDDEN,
+    })?;
+    if let Some(mut target) = players.get(&user).cloned() { //очистити кімнату з сесії вигнаного гравця
+        if target.room.as_ref() == Some(&id) {
+            target.room = None;
+            players.replace(target);
+        }
+    }
+    rooms.replace(room.clone()); //оновити кімнату в таблиці
+    room.start_countdown(db.clone(), Arc::clone(rooms_ptr)).await; //вигнаний гравець міг бути єдиним не готовим
+    match result {
+        Ok::GameOver(players) => room.save_results(db.as_ref(), &players).await, //якщо в грі залишився один гравець, то зберегти результати
+        Ok::Ok => room.watch_turn(db.clone()), //інакше перезапустити таймер, бо хід міг перейти до наступного гравця
+    }
+    Ok(StatusCode::OK)
+}
+
+#[handler]
+pub async fn kick( //функція для вигнання гравця з кімнати
+    Path(path): Path<Kick>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<StatusCode, StatusCode> {
+    remove_player(path, false, req, db.deref(), players_ptr.deref(), rooms_ptr.deref()).await
+}
+
+#[handler]
+pub async fn ban( //функція для вигнання гравця та заборони повторного приєднання
+    Path(path): Path<Kick>,
+    req: &Request,
+    db: Data<&Arc<DatabaseConnection>>,
+    players_ptr: Data<&Arc<RwLock<crate::Players>>>,
+    rooms_ptr: Data<&Arc<RwLock<Rooms>>>,
+) -> Result<StatusCode, StatusCode> {
+    remove_player(path, true, req, db.deref(), players_ptr.deref(), rooms_ptr.deref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rooms() -> Vec<(SortKey, String, String)> { //кімнати з однаковими значеннями ключа та крапкою в назві
+        ["b.x", "a", "b.x", "c", "a", "b.x", "d"].iter().enumerate()
+            .map(|(index, name)| (SortKey::Text(name.to_string()), format!("{:06}", 100 - index), format!("{:06}", 100 - index)))
+            .collect()
+    }
+
+    fn collect_pages(sort: RoomSort, desc: bool, limit: usize) -> Vec<String> { //обхід усіх сторінок за курсорами
+        let mut ids = Vec::new();
+        let mut after = None;
+        loop {
+            let (page, next) = paginate(rooms(), after.as_ref(), desc, limit);
+            assert!(page.len() <= limit);
+            ids.extend(page);
+            match next {
+                Some(cursor) => after = Some(parse_cursor(sort, &cursor).expect("cursor can't be parsed")),
+                None => return ids,
+            }
+        }
+    }
+
+    #[test]
+    fn pages_do_not_repeat_or_skip_rooms() {
+        for desc in [false, true] {
+            for limit in 1..=8 {
+                let ids = collect_pages(RoomSort::Name, desc, limit);
+                let (all, _) = paginate(rooms(), None, desc, usize::MAX - 1);
+                assert_eq!(ids, all, "desc: {desc}, limit: {limit}");
+            }
+        }
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let (page, next) = paginate(rooms(), None, false, 7);
+        assert_eq!(page.len(), 7);
+        assert!(next.is_none());
+    }
+}
@@ -1,13 +1,14 @@
 use poem::{
     handler, http::StatusCode, web::{
-        Data, Json, Path
+        Data, Json, Path, Query
     },
     Response,
 };
-use sea_orm::DatabaseConnection;
+use sea_orm::{prelude::Uuid, DatabaseConnection};
 use std::{ops::Deref, sync::Arc};
 use crate::database::{self, entities::accounts, queries};
-use serde::{ser::SerializeStruct, Serialize};
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use super::matches::Match;
 
 struct User(pub database::entities::accounts::Model); //обернення рядка таблиці accounts у User для нової серіалізації
 
@@ -32,6 +33,10 @@ struct UserStat { //Структура, яка описує статистику
     wins: i32, //кількість виграшів
     loses: i32, //кількість виграшів
     max_points: i16, //максимальна кількість очків за гру
+    rating: f64, //рейтинг
+    rating_deviation: f64, //відхилення рейтингу
+    series_played: i32, //серій зіграно
+    series_won: i32, //серій виграно
 }
 
 impl From<accounts::Model> for UserStat {
@@ -43,6 +48,10 @@ impl From<accounts::Model> for UserStat {
             wins: value.wins,
             loses: value.loses,
             max_points: value.max_points,
+            rating: value.rating,
+            rating_deviation: value.rating_deviation,
+            series_played: value.series_played,
+            series_won: value.series_won,
         }
     }
 }
@@ -68,3 +77,50 @@ pub async fn get_full(Path(id): Path<String>, db: Data<&Arc<DatabaseConnection>>
         .ok_or(StatusCode::NOT_FOUND)?.into(); //перетворення рядка на статистику
     Ok(Response::builder().body(serde_json::to_string(&user).expect("Failed to serialize UserStat"))) //повернення статистики
 }
+
+
+fn limit() -> u64 { 20 } //функція для задання значення за замовченням при десеріалізації
+
+#[derive(Deserialize)]
+struct PageQuery { //структура, яка описує Query параметри пагінації
+    #[serde(default)]
+    after: u64, //кількість записів, які необхідно пропустити
+    #[serde(default = "limit")]
+    limit: u64, //максимальна кількість записів у відповіді
+}
+
+#[handler]
+pub async fn matches(Path(id): Path<String>, query: Query<PageQuery>, db: Data<&Arc<DatabaseConnection>>) -> Result<Json<Vec<Match>>, StatusCode> { //функція доставання історії ігор гравця
+    let db = db.deref().as_ref();
+    let account = queries::accounts::by_uuid_or_login(id.clone())
+        .one(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let matches = queries::matches::by_player(db, account.uuid, query.after, query.limit.min(100)).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(matches.into_iter().map(|(model, players)| Match::new(model, players, None)).collect()))
+}
+
+#[derive(Debug, Serialize)]
+struct LeaderboardEntry { //Структура, що описує рядок таблиці лідерів
+    uuid: Uuid,
+    display_name: String,
+    rating: f64,
+    rating_deviation: f64,
+}
+
+#[handler]
+pub async fn leaderboard(query: Query<PageQuery>, db: Data<&Arc<DatabaseConnection>>) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> { //функція формування таблиці лідерів
+    let db = db.deref().as_ref();
+    let accounts = queries::accounts::leaderboard(query.after, query.limit.min(100))
+        .all(db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(accounts.into_iter().map(|account| LeaderboardEntry {
+        uuid: account.uuid,
+        display_name: account.display_name,
+        rating: account.rating,
+        rating_deviation: account.rating_deviation,
+    }).collect()))
+}
\ No newline at end of file
@@ -5,16 +5,20 @@ mod game;
 mod runtime_storage;
 
 use poem::{
-    get, handler, head, middleware::{ AddData, Cors }, patch, post, EndpointExt, Route
+    delete, get, handler, head, middleware::{ AddData, Cors }, patch, post, EndpointExt, Route
 };
 use shuttle_poem::ShuttlePoem;
 use shuttle_runtime::SecretStore;
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 use http::*;
 
 pub type Players = HashSet::<gateway::sessions::User>;
 pub type Rooms = runtime_storage::DataTable::<game::rooms::Room>;
+pub type Queue = game::matchmaking::Queue;
+pub type Lobby = gateway::lobby::Lobby;
+
+const DEFAULT_ROOM_IDLE_TIMEOUT: u64 = 1800; //Час без активності, після якого кімната закривається, якщо ROOM_IDLE_TIMEOUT не задано
 
 #[handler]
 fn hello_world() -> &'static str {
@@ -36,7 +40,10 @@ async fn poem(#[shuttle_runtime::Secrets] secret_store: SecretStore) -> ShuttleP
                 user, pass, host, port, name
             );
             match sea_orm::Database::connect(uri).await { //Спроба з'єднатися
-                Ok(connection) => Ok(connection), //при з'єднані повернути у змінну db з'єднання
+                Ok(connection) => match database::migrations::run(&connection).await { //при з'єднані застосувати міграції схеми
+                    Ok(()) => Ok(connection), //та повернути у змінну db з'єднання
+                    Err(e) => Err(shuttle_runtime::Error::Database(e.to_string())),
+                },
                 Err(e) => Err(shuttle_runtime::Error::Database(shuttle_runtime::CustomError::new(e).to_string())), //В іншому випадку повернути у змінну db помилку
             }
         },
@@ -45,6 +52,16 @@ async fn poem(#[shuttle_runtime::Secrets] secret_store: SecretStore) -> ShuttleP
 
     match db {
         Ok(db) => { //Якщо змінна db містить з'єднання
+            let db = Arc::new(db);
+            let players = Arc::new(RwLock::new(Players::new())); //список авторизованих по gateway гравців
+            let rooms = Arc::new(RwLock::new(Rooms::new())); //список кімнат
+            let queue = Arc::new(RwLock::new(Queue::new())); //черга пошуку гри
+            let (lobby, lobby_changes) = Lobby::new();
+            let lobby = Arc::new(RwLock::new(lobby)); //підписники на список публічних кімнат
+            tokio::spawn(gateway::lobby::run(lobby.clone(), lobby_changes)); //оголошення змін кімнат підписникам лобі по черзі
+            tokio::spawn(game::matchmaking::run(queue.clone(), db.clone(), players.clone(), rooms.clone(), lobby.clone())); //фоновий підбір гравців з черги
+            let room_idle_timeout = secret_store.get("ROOM_IDLE_TIMEOUT").and_then(|value| value.parse().ok()).unwrap_or(DEFAULT_ROOM_IDLE_TIMEOUT); //час без активності в секундах
+            tokio::spawn(game::rooms::reaper::run(players.clone(), rooms.clone(), Duration::from_secs(room_idle_timeout))); //фонове закриття неактивних кімнат
             let app = Route::new() //Тоді створити новий екземпляр Route
             .at("/api/hello_world", get(hello_world)) //Задавання шляхів, методів та відповідних функцій
             .at("/api/gateway", get(gateway::gateway))
@@ -54,18 +71,31 @@ async fn poem(#[shuttle_runtime::Secrets] secret_store: SecretStore) -> ShuttleP
             .at("/api/auth/logout_all", post(auth::logout_all))
             .at("/api/users/:id", get(users::get))
             .at("/api/users/:id/stat", get(users::get_full))
+            .at("/api/users/:id/matches", get(users::matches))
+            .at("/api/matches/:id", get(matches::get))
+            .at("/api/leaderboard", get(users::leaderboard))
+            .at("/api/matchmaking", post(matchmaking::join).delete(matchmaking::leave))
             .at("/api/rooms", get(http::rooms::get_rooms_list).post(http::rooms::create))
             .at("/api/rooms/:id", patch(http::rooms::update))
             .at("/api/rooms/:id/join", post(http::rooms::join))
             .at("/api/rooms/:id/ready", post(http::rooms::ready))
             .at("/api/rooms/:id/leave", post(http::rooms::leave))
+            .at("/api/rooms/:id/bots", post(http::rooms::add_bot))
+            .at("/api/rooms/:id/kick/:user", post(http::rooms::kick))
+            .at("/api/rooms/:id/ban/:user", post(http::rooms::ban))
+            .at("/api/rooms/:id/invites", get(http::rooms::invites::list).post(http::rooms::invites::create))
+            .at("/api/rooms/:id/invites/:code", delete(http::rooms::invites::revoke))
+            .at("/api/invites/:code", post(http::rooms::invites::redeem))
+            .at("/api/rooms/:id/spectate", post(http::rooms::spectate).delete(http::rooms::stop_spectating))
             .at("/api/rooms/:id/game", get(http::rooms::game::get).post(http::rooms::game::start))
             .at("/api/rooms/:id/game/play", post(http::rooms::game::play))
             .at("/api/rooms/:id/game/play/:card_id", post(http::rooms::game::play))
             .with(Cors::new().allow_origin_regex("*")) //Налаштування CORS політики
-            .with(AddData::new(Arc::new(db))) //Передача посилання на з'єднання БД в аргументи функцій
-            .with(AddData::new(Arc::new(RwLock::new(Players::new())))) //Передача посилання на список авторизованих по gateway гравців
-            .with(AddData::new(Arc::new(RwLock::new(Rooms::new())))); //Передача посилання на список кімнат
+            .with(AddData::new(db)) //Передача посилання на з'єднання БД в аргументи функцій
+            .with(AddData::new(players)) //Передача посилання на список авторизованих по gateway гравців
+            .with(AddData::new(rooms)) //Передача посилання на список кімнат
+            .with(AddData::new(queue)) //Передача посилання на чергу пошуку гри
+            .with(AddData::new(lobby)); //Передача посилання на лобі
             Ok(app.into()) //Завершення налаштування та передача Route в Shuttle Runtime.
         }
         Err(e) => {